@@ -25,6 +25,15 @@ pub struct DatabaseConfig {
 pub struct StorageConfig {
     pub chunk_size_mb: u64,
     pub temp_dir: PathBuf,
+    /// Multipart uploads older than this are aborted by the maintenance task (0 = never)
+    pub multipart_max_age_hours: u64,
+    /// How often background maintenance runs
+    pub maintenance_interval_secs: u64,
+    /// Free draft slots kept for recycling; drafts beyond this are expunged in one
+    /// batch (0 = keep all, never expunge)
+    pub free_pool_max_chunks: u64,
+    /// Objects deleted from unversioned buckets stay restorable for this long (0 = no trash)
+    pub trash_retention_hours: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -32,6 +41,14 @@ pub struct S3Config {
     pub access_key_id: String,
     pub secret_access_key: String,
     pub region: String,
+    /// Base domain for virtual-hosted-style requests (`{bucket}.{domain}`), if enabled
+    pub domain: Option<String>,
+    /// Lifetime of STS credentials when the request doesn't ask for one
+    pub sts_default_duration_secs: u64,
+    /// Longest lifetime an STS request may ask for (DurationSeconds)
+    pub sts_max_duration_secs: u64,
+    /// Accept legacy SigV2 (HMAC-SHA1) signatures alongside SigV4
+    pub sigv2_enabled: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -65,6 +82,18 @@ impl AppConfig {
                 temp_dir: PathBuf::from(
                     std::env::var("STORAGE_TEMP_DIR").unwrap_or_else(|_| "./tmp".to_string()),
                 ),
+                multipart_max_age_hours: std::env::var("STORAGE_MULTIPART_MAX_AGE_HOURS")
+                    .unwrap_or_else(|_| "168".to_string())
+                    .parse()?,
+                maintenance_interval_secs: std::env::var("STORAGE_MAINTENANCE_INTERVAL_SECS")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()?,
+                free_pool_max_chunks: std::env::var("STORAGE_FREE_POOL_MAX_CHUNKS")
+                    .unwrap_or_else(|_| "100".to_string())
+                    .parse()?,
+                trash_retention_hours: std::env::var("STORAGE_TRASH_RETENTION_HOURS")
+                    .unwrap_or_else(|_| "72".to_string())
+                    .parse()?,
             },
             s3: S3Config {
                 access_key_id: std::env::var("S3_ACCESS_KEY_ID")
@@ -72,6 +101,16 @@ impl AppConfig {
                 secret_access_key: std::env::var("S3_SECRET_ACCESS_KEY")
                     .unwrap_or_else(|_| "objectmail-secret-key".to_string()),
                 region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                domain: std::env::var("S3_DOMAIN").ok().filter(|d| !d.is_empty()),
+                sts_default_duration_secs: std::env::var("S3_STS_DEFAULT_DURATION_SECS")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()?,
+                sts_max_duration_secs: std::env::var("S3_STS_MAX_DURATION_SECS")
+                    .unwrap_or_else(|_| "43200".to_string())
+                    .parse()?,
+                sigv2_enabled: std::env::var("S3_SIGV2_ENABLED")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()?,
             },
             email: EmailConfig {
                 provider: std::env::var("EMAIL_PROVIDER").unwrap_or_else(|_| "gmail".to_string()),
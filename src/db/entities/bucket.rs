@@ -11,6 +11,18 @@ pub struct Model {
     pub owner_id: String,
     pub region: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub lifecycle: Option<serde_json::Value>,
+    /// None if versioning was never enabled, otherwise "Enabled" or "Suspended"
+    pub versioning: Option<String>,
+    /// Bucket policy document (PutBucketPolicy)
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub policy: Option<serde_json::Value>,
+    /// Canned ACL: "private" or "public-read"
+    pub acl: String,
+    /// CORS configuration (PutBucketCors)
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub cors: Option<serde_json::Value>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,6 +1,10 @@
+pub mod access_key;
 pub mod bucket;
 pub mod chunk;
 pub mod email_account;
 pub mod multipart_part;
+pub mod multipart_part_chunk;
 pub mod multipart_upload;
 pub mod object;
+pub mod session_credential;
+pub mod user;
@@ -13,6 +13,8 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub temp_path: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Base64 checksum of the part, in the upload's checksum algorithm
+    pub checksum: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +25,8 @@ pub enum Relation {
         to = "super::multipart_upload::Column::Id"
     )]
     MultipartUpload,
+    #[sea_orm(has_many = "super::multipart_part_chunk::Entity")]
+    Chunks,
 }
 
 impl Related<super::multipart_upload::Entity> for Entity {
@@ -31,4 +35,10 @@ impl Related<super::multipart_upload::Entity> for Entity {
     }
 }
 
+impl Related<super::multipart_part_chunk::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Chunks.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
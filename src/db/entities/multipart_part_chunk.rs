@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A draft referenced by a staged multipart part (e.g. from UploadPartCopy).
+/// Counts as a reference to the draft until the upload is completed or aborted.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "multipart_part_chunks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub chunk_index: i32,
+    pub size: i64,
+    pub hash: String,
+    pub draft_uid: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::multipart_part::Entity",
+        from = "Column::PartId",
+        to = "super::multipart_part::Column::Id"
+    )]
+    MultipartPart,
+}
+
+impl Related<super::multipart_part::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MultipartPart.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
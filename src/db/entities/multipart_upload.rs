@@ -13,6 +13,16 @@ pub struct Model {
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub metadata: Option<serde_json::Value>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Tags from `x-amz-tagging`, applied to the object on completion
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub tags: Option<serde_json::Value>,
+    /// Checksum algorithm from `x-amz-checksum-algorithm`, computed for every part
+    pub checksum_algorithm: Option<String>,
+    /// Standard HTTP headers, applied to the object on completion
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub headers: Option<serde_json::Value>,
+    /// Base64 MD5 of the customer-provided key (SSE-C) every part must be uploaded with
+    pub sse_customer_key_md5: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -17,6 +17,24 @@ pub struct Model {
     pub chunk_count: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// "null" for objects written while versioning was never enabled or suspended
+    pub version_id: String,
+    pub is_latest: bool,
+    pub is_delete_marker: bool,
+    /// Set while the object is in the trash (soft-deleted, restorable until purged)
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Object tags as a JSON object of key → value
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub tags: Option<serde_json::Value>,
+    /// "CRC32", "CRC32C", "SHA1" or "SHA256", if the client asked for a checksum
+    pub checksum_algorithm: Option<String>,
+    /// Base64 checksum; `<checksum>-<parts>` for multipart objects
+    pub checksum: Option<String>,
+    /// Standard HTTP headers given on upload (e.g. `cache-control`), as a JSON object of name → value
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub headers: Option<serde_json::Value>,
+    /// Base64 MD5 of the customer-provided key (SSE-C) the chunks are encrypted with
+    pub sse_customer_key_md5: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
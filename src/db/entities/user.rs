@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "users")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub name: String,
+    /// Disabled users can't authenticate with any of their keys
+    pub disabled: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Identity policy document limiting or extending what the user may do
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub policy: Option<serde_json::Value>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::access_key::Entity")]
+    AccessKeys,
+}
+
+impl Related<super::access_key::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccessKeys.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -332,6 +332,46 @@ impl EmailProvider for GmailProvider {
         Ok(())
     }
 
+    async fn delete_drafts(&self, uids: &[u32]) -> Result<()> {
+        if uids.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_session().await?;
+        let mut guard = self.session.lock().await;
+        let session = guard.as_mut().context("No IMAP session")?;
+
+        session
+            .select(&self.drafts_folder)
+            .await
+            .context("Failed to SELECT drafts folder")?;
+
+        // Mark all as deleted (in batches to keep command lines short)
+        for batch in uids.chunks(500) {
+            let uid_set = batch
+                .iter()
+                .map(|uid| uid.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            let store_stream = session
+                .uid_store(uid_set, "+FLAGS (\\Deleted)")
+                .await
+                .context("IMAP UID STORE failed")?;
+            tokio::pin!(store_stream);
+            while store_stream.next().await.is_some() {}
+        }
+
+        // One expunge for the whole set
+        {
+            let expunge_stream = session.expunge().await.context("IMAP EXPUNGE failed")?;
+            tokio::pin!(expunge_stream);
+            while expunge_stream.next().await.is_some() {}
+        }
+
+        tracing::info!("{} drafts deleted", uids.len());
+        Ok(())
+    }
+
     async fn health_check(&self) -> Result<()> {
         self.ensure_session().await?;
         let mut guard = self.session.lock().await;
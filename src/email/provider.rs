@@ -15,6 +15,64 @@ pub trait EmailProvider: Send + Sync {
     /// Delete a draft by its IMAP UID.
     async fn delete_draft(&self, uid: u32) -> Result<()>;
 
+    /// Delete several drafts at once.
+    /// Providers should override this to flag and expunge in a single round trip.
+    async fn delete_drafts(&self, uids: &[u32]) -> Result<()> {
+        for &uid in uids {
+            self.delete_draft(uid).await?;
+        }
+        Ok(())
+    }
+
     /// Check connectivity / health
     async fn health_check(&self) -> Result<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records deletes; fails on one UID
+    struct RecordingProvider {
+        deleted: Mutex<Vec<u32>>,
+        failing_uid: u32,
+    }
+
+    #[async_trait]
+    impl EmailProvider for RecordingProvider {
+        async fn create_draft(&self, _subject: &str, _attachment_data: &[u8]) -> Result<u32> {
+            unimplemented!()
+        }
+
+        async fn get_draft(&self, _uid: u32) -> Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        async fn delete_draft(&self, uid: u32) -> Result<()> {
+            if uid == self.failing_uid {
+                anyhow::bail!("draft {} is gone", uid);
+            }
+            self.deleted.lock().unwrap().push(uid);
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_delete_drafts() {
+        let provider = RecordingProvider {
+            deleted: Mutex::new(Vec::new()),
+            failing_uid: 3,
+        };
+        provider.delete_drafts(&[1, 2]).await.unwrap();
+        assert_eq!(*provider.deleted.lock().unwrap(), vec![1, 2]);
+
+        // Stops at the first failure
+        assert!(provider.delete_drafts(&[4, 3, 5]).await.is_err());
+        assert_eq!(*provider.deleted.lock().unwrap(), vec![1, 2, 4]);
+    }
+}
@@ -5,8 +5,10 @@ mod migration;
 mod s3;
 mod storage;
 
+use axum::ServiceExt;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tower::Layer;
 
 use config::AppConfig;
 use email::gmail::GmailProvider;
@@ -78,8 +80,16 @@ async fn main() -> anyhow::Result<()> {
         pipeline: Arc::new(Mutex::new(pipeline)),
     };
 
-    // Build router
+    // Start background maintenance (stale multipart uploads, etc.)
+    storage::maintenance::spawn(state.clone());
+
+    // Build router; virtual-hosted-style requests are rewritten before routing
     let app = s3::router::build_router(state);
+    let app = axum::middleware::from_fn_with_state(
+        config.s3.domain.clone(),
+        s3::router::virtual_host_middleware,
+    )
+    .layer(app);
 
     // Start server
     let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -90,7 +100,14 @@ async fn main() -> anyhow::Result<()> {
         config.server.port
     );
 
-    axum::serve(listener, app).await?;
+    // Connection info gives policies the client address (aws:SourceIp)
+    axum::serve(
+        listener,
+        ServiceExt::<axum::extract::Request>::into_make_service_with_connect_info::<
+            std::net::SocketAddr,
+        >(app),
+    )
+    .await?;
 
     Ok(())
 }
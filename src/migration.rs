@@ -5,7 +5,24 @@ pub struct Migrator;
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(Migration001CreateTables)]
+        vec![
+            Box::new(Migration001CreateTables),
+            Box::new(Migration002AddBucketLifecycle),
+            Box::new(Migration003CreateMultipartPartChunks),
+            Box::new(Migration004ObjectKeyByteOrder),
+            Box::new(Migration005AddVersioning),
+            Box::new(Migration006AddObjectTrash),
+            Box::new(Migration007AddObjectTags),
+            Box::new(Migration008AddMultipartUploadTags),
+            Box::new(Migration009AddChecksums),
+            Box::new(Migration010AddObjectHeaders),
+            Box::new(Migration011CreateUsers),
+            Box::new(Migration012AddPolicies),
+            Box::new(Migration013CreateSessionCredentials),
+            Box::new(Migration014AddBucketAcl),
+            Box::new(Migration015AddBucketCors),
+            Box::new(Migration016AddSseCustomerKey),
+        ]
     }
 }
 
@@ -339,6 +356,956 @@ impl MigrationTrait for Migration001CreateTables {
     }
 }
 
+pub struct Migration002AddBucketLifecycle;
+
+impl MigrationName for Migration002AddBucketLifecycle {
+    fn name(&self) -> &str {
+        "m002_add_bucket_lifecycle"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration002AddBucketLifecycle {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Per-bucket lifecycle configuration (stored as JSON)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Buckets::Lifecycle).json_binary().null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .drop_column(Buckets::Lifecycle)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration003CreateMultipartPartChunks;
+
+impl MigrationName for Migration003CreateMultipartPartChunks {
+    fn name(&self) -> &str {
+        "m003_create_multipart_part_chunks"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration003CreateMultipartPartChunks {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // multipart_part_chunks table: drafts referenced by a staged part (UploadPartCopy)
+        manager
+            .create_table(
+                Table::create()
+                    .table(MultipartPartChunks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::PartId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::ChunkIndex)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::Size)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::Hash)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MultipartPartChunks::DraftUid)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MultipartPartChunks::Table, MultipartPartChunks::PartId)
+                            .to(MultipartParts::Table, MultipartParts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Unique index on (part_id, chunk_index)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_multipart_part_chunks_part_index")
+                    .table(MultipartPartChunks::Table)
+                    .col(MultipartPartChunks::PartId)
+                    .col(MultipartPartChunks::ChunkIndex)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MultipartPartChunks::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct Migration004ObjectKeyByteOrder;
+
+impl MigrationName for Migration004ObjectKeyByteOrder {
+    fn name(&self) -> &str {
+        "m004_object_key_byte_order"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration004ObjectKeyByteOrder {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // S3 lists keys in UTF-8 byte order; the "C" collation makes ORDER BY and
+        // range comparisons on objects.key match it (and rebuilds the key index to suit)
+        manager
+            .get_connection()
+            .execute_unprepared(r#"ALTER TABLE objects ALTER COLUMN key TYPE text COLLATE "C""#)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE objects ALTER COLUMN key TYPE text COLLATE "default""#,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct Migration005AddVersioning;
+
+impl MigrationName for Migration005AddVersioning {
+    fn name(&self) -> &str {
+        "m005_add_versioning"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration005AddVersioning {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bucket versioning state: NULL (never enabled), 'Enabled' or 'Suspended'
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Buckets::Versioning).string_len(16).null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Objects become object versions; existing rows are the 'null' version
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::VersionId)
+                            .string_len(64)
+                            .not_null()
+                            .default("null"),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::IsLatest)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::IsDeleteMarker)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_bucket_key")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every version of a key is unique by its version id...
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_objects_bucket_key_version")
+                    .table(Objects::Table)
+                    .col(Objects::BucketId)
+                    .col(Objects::Key)
+                    .col(Objects::VersionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // ...and only one of them is the current version
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_objects_bucket_key_latest \
+                 ON objects (bucket_id, key) WHERE is_latest",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Keep only the current, non-deleted version of each key
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM objects WHERE NOT is_latest OR is_delete_marker")
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_bucket_key_latest")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_bucket_key_version")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_objects_bucket_key")
+                    .table(Objects::Table)
+                    .col(Objects::BucketId)
+                    .col(Objects::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::VersionId)
+                    .drop_column(Objects::IsLatest)
+                    .drop_column(Objects::IsDeleteMarker)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .drop_column(Buckets::Versioning)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration006AddObjectTrash;
+
+impl MigrationName for Migration006AddObjectTrash {
+    fn name(&self) -> &str {
+        "m006_add_object_trash"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration006AddObjectTrash {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Soft-deleted objects are kept (with their chunks) until purged
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A trashed version must not block re-creating the same key and version
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_bucket_key_version")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_objects_bucket_key_version \
+                 ON objects (bucket_id, key, version_id) WHERE deleted_at IS NULL",
+            )
+            .await?;
+
+        // For the purge job
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX IF NOT EXISTS idx_objects_deleted_at \
+                 ON objects (deleted_at) WHERE deleted_at IS NOT NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM objects WHERE deleted_at IS NOT NULL")
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_deleted_at")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_objects_bucket_key_version")
+                    .table(Objects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_objects_bucket_key_version")
+                    .table(Objects::Table)
+                    .col(Objects::BucketId)
+                    .col(Objects::Key)
+                    .col(Objects::VersionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration007AddObjectTags;
+
+impl MigrationName for Migration007AddObjectTags {
+    fn name(&self) -> &str {
+        "m007_add_object_tags"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration007AddObjectTags {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Objects::Tags).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::Tags)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration008AddMultipartUploadTags;
+
+impl MigrationName for Migration008AddMultipartUploadTags {
+    fn name(&self) -> &str {
+        "m008_add_multipart_upload_tags"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration008AddMultipartUploadTags {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Tags given to CreateMultipartUpload, applied to the object on completion
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(MultipartUploads::Tags).json_binary().null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .drop_column(MultipartUploads::Tags)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration009AddChecksums;
+
+impl MigrationName for Migration009AddChecksums {
+    fn name(&self) -> &str {
+        "m009_add_checksums"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration009AddChecksums {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Optional CRC32/CRC32C/SHA1/SHA256 checksum requested by the client
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::ChecksumAlgorithm)
+                            .string_len(16)
+                            .null(),
+                    )
+                    .add_column_if_not_exists(ColumnDef::new(Objects::Checksum).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(MultipartUploads::ChecksumAlgorithm)
+                            .string_len(16)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartParts::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(MultipartParts::Checksum).string().null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartParts::Table)
+                    .drop_column(MultipartParts::Checksum)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .drop_column(MultipartUploads::ChecksumAlgorithm)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::ChecksumAlgorithm)
+                    .drop_column(Objects::Checksum)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration010AddObjectHeaders;
+
+impl MigrationName for Migration010AddObjectHeaders {
+    fn name(&self) -> &str {
+        "m010_add_object_headers"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration010AddObjectHeaders {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Standard HTTP headers (Content-Encoding, Cache-Control, ...) returned on GET/HEAD
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Objects::Headers).json_binary().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(MultipartUploads::Headers)
+                            .json_binary()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .drop_column(MultipartUploads::Headers)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::Headers)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration011CreateUsers;
+
+impl MigrationName for Migration011CreateUsers {
+    fn name(&self) -> &str {
+        "m011_create_users"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration011CreateUsers {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // users table: people and CI jobs that own buckets
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Users::Id).uuid().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Users::Name)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Users::Disabled)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Users::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // access_keys table: SigV4 credentials, several per user
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessKeys::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessKeys::AccessKeyId)
+                            .string_len(32)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AccessKeys::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AccessKeys::SecretAccessKey)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessKeys::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("Active"),
+                    )
+                    .col(
+                        ColumnDef::new(AccessKeys::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccessKeys::Table, AccessKeys::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessKeys::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Users::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct Migration012AddPolicies;
+
+impl MigrationName for Migration012AddPolicies {
+    fn name(&self) -> &str {
+        "m012_add_policies"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration012AddPolicies {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bucket policy documents (PutBucketPolicy)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Buckets::Policy).json_binary().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Identity policies attached to users through the admin API
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Users::Policy).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Policy)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .drop_column(Buckets::Policy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration013CreateSessionCredentials;
+
+impl MigrationName for Migration013CreateSessionCredentials {
+    fn name(&self) -> &str {
+        "m013_create_session_credentials"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration013CreateSessionCredentials {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // session_credentials table: temporary keys issued by STS.
+        // user_id is NULL for sessions of the root credential.
+        manager
+            .create_table(
+                Table::create()
+                    .table(SessionCredentials::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SessionCredentials::AccessKeyId)
+                            .string_len(32)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SessionCredentials::UserId).uuid().null())
+                    .col(
+                        ColumnDef::new(SessionCredentials::SecretAccessKey)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCredentials::SessionToken)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCredentials::Policy)
+                            .json_binary()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCredentials::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCredentials::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SessionCredentials::Table, SessionCredentials::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Expired sessions are purged by the maintenance task
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_session_credentials_expires_at")
+                    .table(SessionCredentials::Table)
+                    .col(SessionCredentials::ExpiresAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SessionCredentials::Table).to_owned())
+            .await
+    }
+}
+
+pub struct Migration014AddBucketAcl;
+
+impl MigrationName for Migration014AddBucketAcl {
+    fn name(&self) -> &str {
+        "m014_add_bucket_acl"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration014AddBucketAcl {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Canned ACL: "private" or "public-read"
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Buckets::Acl)
+                            .string_len(32)
+                            .not_null()
+                            .default("private"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .drop_column(Buckets::Acl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration015AddBucketCors;
+
+impl MigrationName for Migration015AddBucketCors {
+    fn name(&self) -> &str {
+        "m015_add_bucket_cors"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration015AddBucketCors {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Per-bucket CORS configuration (stored as JSON)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Buckets::Cors).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Buckets::Table)
+                    .drop_column(Buckets::Cors)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+pub struct Migration016AddSseCustomerKey;
+
+impl MigrationName for Migration016AddSseCustomerKey {
+    fn name(&self) -> &str {
+        "m016_add_sse_customer_key"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration016AddSseCustomerKey {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Base64 MD5 of the customer-provided key (SSE-C) the data is encrypted with
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Objects::SseCustomerKeyMd5)
+                            .string_len(32)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(MultipartUploads::SseCustomerKeyMd5)
+                            .string_len(32)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MultipartUploads::Table)
+                    .drop_column(MultipartUploads::SseCustomerKeyMd5)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Objects::Table)
+                    .drop_column(Objects::SseCustomerKeyMd5)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
 // ========== Table identifiers ==========
 
 #[derive(Iden)]
@@ -363,6 +1330,11 @@ enum Buckets {
     OwnerId,
     Region,
     CreatedAt,
+    Lifecycle,
+    Versioning,
+    Policy,
+    Acl,
+    Cors,
 }
 
 #[derive(Iden)]
@@ -378,6 +1350,15 @@ enum Objects {
     ChunkCount,
     CreatedAt,
     UpdatedAt,
+    VersionId,
+    IsLatest,
+    IsDeleteMarker,
+    DeletedAt,
+    Tags,
+    ChecksumAlgorithm,
+    Checksum,
+    Headers,
+    SseCustomerKeyMd5,
 }
 
 #[derive(Iden)]
@@ -404,6 +1385,10 @@ enum MultipartUploads {
     ContentType,
     Metadata,
     CreatedAt,
+    Tags,
+    ChecksumAlgorithm,
+    Headers,
+    SseCustomerKeyMd5,
 }
 
 #[derive(Iden)]
@@ -416,4 +1401,48 @@ enum MultipartParts {
     Etag,
     TempPath,
     CreatedAt,
+    Checksum,
+}
+
+#[derive(Iden)]
+enum MultipartPartChunks {
+    Table,
+    Id,
+    PartId,
+    ChunkIndex,
+    Size,
+    Hash,
+    DraftUid,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    Name,
+    Disabled,
+    CreatedAt,
+    Policy,
+}
+
+#[derive(Iden)]
+enum AccessKeys {
+    Table,
+    AccessKeyId,
+    UserId,
+    SecretAccessKey,
+    Status,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum SessionCredentials {
+    Table,
+    AccessKeyId,
+    UserId,
+    SecretAccessKey,
+    SessionToken,
+    Policy,
+    ExpiresAt,
+    CreatedAt,
 }
@@ -0,0 +1,210 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+
+use crate::db::entities::bucket;
+use crate::s3::error::S3Error;
+use crate::s3::xml;
+use crate::AppState;
+
+/// Only the owner (and whoever a policy allows) has access; the default
+pub const ACL_PRIVATE: &str = "private";
+/// Everyone, including anonymous clients, may list the bucket and read its objects
+pub const ACL_PUBLIC_READ: &str = "public-read";
+
+/// Grantee URI of the "everyone" group
+const ALL_USERS_URI: &str = "http://acs.amazonaws.com/groups/global/AllUsers";
+/// Namespace of the `xsi:type` attribute on grantees
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+/// Actions a public-read bucket allows for everyone
+const PUBLIC_READ_ACTIONS: &[&str] = &["s3:ListBucket", "s3:GetObject"];
+
+/// GET /{bucket}?acl — Return the bucket ACL
+pub async fn get_bucket_acl(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let xml_body = xml::to_xml(&access_control_policy(&bucket))
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// PUT /{bucket}?acl — Set the bucket ACL, from `x-amz-acl` or an AccessControlPolicy body
+pub async fn put_bucket_acl(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let acl = match canned_acl_from_header(&headers)? {
+        Some(acl) => acl,
+        None if body.is_empty() => {
+            return Err(S3Error::InvalidRequest(
+                "PutBucketAcl needs an x-amz-acl header or an AccessControlPolicy body".to_string(),
+            ))
+        }
+        None => {
+            let body_str = std::str::from_utf8(&body)
+                .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+            let policy: xml::AccessControlPolicy = xml::from_xml(body_str).map_err(|e| {
+                S3Error::MalformedXML(format!("Failed to parse AccessControlPolicy XML: {}", e))
+            })?;
+            canned_acl_from_policy(&policy, &bucket.owner_id)?
+        }
+    };
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.acl = Set(acl.to_string());
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    Ok(StatusCode::OK.into_response())
+}
+
+/// Canned ACL requested with `x-amz-acl`, if any
+pub fn canned_acl_from_header(headers: &HeaderMap) -> Result<Option<&'static str>, S3Error> {
+    match headers.get("x-amz-acl").map(|v| v.to_str().unwrap_or("")) {
+        None => Ok(None),
+        Some(ACL_PRIVATE) => Ok(Some(ACL_PRIVATE)),
+        Some(ACL_PUBLIC_READ) => Ok(Some(ACL_PUBLIC_READ)),
+        Some(other) => Err(S3Error::NotImplemented(format!(
+            "Canned ACL '{}' is not supported; use private or public-read",
+            other
+        ))),
+    }
+}
+
+/// Map a full AccessControlPolicy onto the canned ACLs we support: the
+/// owner's FULL_CONTROL grant is implied, and a READ grant to AllUsers makes
+/// the bucket public-read. Any other grant can't be represented.
+fn canned_acl_from_policy(
+    policy: &xml::AccessControlPolicy,
+    owner_id: &str,
+) -> Result<&'static str, S3Error> {
+    let mut acl = ACL_PRIVATE;
+    for grant in &policy.access_control_list.grants {
+        let grantee = &grant.grantee;
+        if grantee.uri.as_deref() == Some(ALL_USERS_URI) && grant.permission == "READ" {
+            acl = ACL_PUBLIC_READ;
+        } else if grantee.id.as_deref() != Some(owner_id) || grant.permission != "FULL_CONTROL" {
+            return Err(S3Error::NotImplemented(
+                "Only grants matching the private or public-read canned ACLs are supported"
+                    .to_string(),
+            ));
+        }
+    }
+    Ok(acl)
+}
+
+/// The bucket's canned ACL spelled out as grants
+fn access_control_policy(bucket: &bucket::Model) -> xml::AccessControlPolicy {
+    let mut grants = vec![xml::Grant {
+        grantee: xml::Grantee {
+            xmlns_xsi: Some(XSI_NAMESPACE.to_string()),
+            grantee_type: "CanonicalUser".to_string(),
+            id: Some(bucket.owner_id.clone()),
+            display_name: Some(bucket.owner_id.clone()),
+            uri: None,
+        },
+        permission: "FULL_CONTROL".to_string(),
+    }];
+    if bucket.acl == ACL_PUBLIC_READ {
+        grants.push(xml::Grant {
+            grantee: xml::Grantee {
+                xmlns_xsi: Some(XSI_NAMESPACE.to_string()),
+                grantee_type: "Group".to_string(),
+                id: None,
+                display_name: None,
+                uri: Some(ALL_USERS_URI.to_string()),
+            },
+            permission: "READ".to_string(),
+        });
+    }
+
+    xml::AccessControlPolicy {
+        owner: Some(xml::Owner {
+            id: bucket.owner_id.clone(),
+            display_name: bucket.owner_id.clone(),
+        }),
+        access_control_list: xml::AccessControlList { grants },
+    }
+}
+
+/// Whether the bucket's ACL lets anyone perform `action`
+pub fn grants_public_access(bucket: &bucket::Model, action: &str) -> bool {
+    bucket.acl == ACL_PUBLIC_READ && PUBLIC_READ_ACTIONS.contains(&action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canned_acl_from_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(canned_acl_from_header(&headers).unwrap(), None);
+
+        headers.insert("x-amz-acl", "public-read".parse().unwrap());
+        assert_eq!(
+            canned_acl_from_header(&headers).unwrap(),
+            Some(ACL_PUBLIC_READ)
+        );
+
+        headers.insert("x-amz-acl", "public-read-write".parse().unwrap());
+        assert!(canned_acl_from_header(&headers).is_err());
+    }
+
+    #[test]
+    fn test_canned_acl_from_policy() {
+        let body = r#"<AccessControlPolicy>
+            <Owner><ID>owner</ID></Owner>
+            <AccessControlList>
+                <Grant>
+                    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="CanonicalUser">
+                        <ID>owner</ID>
+                    </Grantee>
+                    <Permission>FULL_CONTROL</Permission>
+                </Grant>
+                <Grant>
+                    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="Group">
+                        <URI>http://acs.amazonaws.com/groups/global/AllUsers</URI>
+                    </Grantee>
+                    <Permission>READ</Permission>
+                </Grant>
+            </AccessControlList>
+        </AccessControlPolicy>"#;
+        let policy: xml::AccessControlPolicy = xml::from_xml(body).unwrap();
+        assert_eq!(
+            canned_acl_from_policy(&policy, "owner").unwrap(),
+            ACL_PUBLIC_READ
+        );
+
+        // A grant to somebody else can't be expressed as a canned ACL
+        assert!(canned_acl_from_policy(&policy, "someone-else").is_err());
+    }
+}
@@ -0,0 +1,526 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::entities::{access_key, bucket, object, user};
+use crate::s3::error::S3Error;
+use crate::s3::identity::{self, KEY_ACTIVE, KEY_INACTIVE};
+use crate::s3::policy::Policy;
+use crate::AppState;
+
+/// Maximum number of trashed objects returned by one listing
+const TRASH_LIST_LIMIT: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    pub bucket: Option<String>,
+    pub prefix: Option<String>,
+}
+
+/// A trashed object, as returned by the admin API
+#[derive(Debug, Serialize)]
+pub struct TrashEntry {
+    pub id: Uuid,
+    pub bucket: String,
+    pub key: String,
+    pub size: i64,
+    pub etag: String,
+    pub deleted_at: DateTime<Utc>,
+    /// When the purge job will remove the object for good
+    pub expires_at: DateTime<Utc>,
+}
+
+/// GET /_admin/trash — List trashed objects, most recently deleted first
+pub async fn list_trash(
+    State(state): State<AppState>,
+    Query(params): Query<TrashQuery>,
+) -> Result<Response, S3Error> {
+    let mut query = object::Entity::find().filter(object::Column::DeletedAt.is_not_null());
+
+    if let Some(ref bucket_name) = params.bucket {
+        let bucket = bucket::Entity::find()
+            .filter(bucket::Column::Name.eq(bucket_name))
+            .one(&state.db)
+            .await
+            .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+            .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+        query = query.filter(object::Column::BucketId.eq(bucket.id));
+    }
+    if let Some(ref prefix) = params.prefix {
+        query = query.filter(object::Column::Key.starts_with(prefix));
+    }
+
+    let trashed = query
+        .order_by_desc(object::Column::DeletedAt)
+        .limit(TRASH_LIST_LIMIT)
+        .all(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    let bucket_names: HashMap<Uuid, String> = bucket::Entity::find()
+        .filter(bucket::Column::Id.is_in(trashed.iter().map(|o| o.bucket_id)))
+        .all(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .into_iter()
+        .map(|b| (b.id, b.name))
+        .collect();
+
+    let retention = Duration::hours(state.config.storage.trash_retention_hours as i64);
+    let entries: Vec<TrashEntry> = trashed
+        .into_iter()
+        .filter_map(|obj| trash_entry(obj, &bucket_names, retention))
+        .collect();
+
+    Ok(Json(entries).into_response())
+}
+
+/// IAM user name rules; "anonymous" is reserved for unsigned requests
+fn check_user_name(name: &str) -> Result<(), S3Error> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+=,.@_-".contains(c));
+    if !valid {
+        return Err(S3Error::InvalidArgument(format!(
+            "Invalid user name '{}': use up to 64 letters, digits and +=,.@_-",
+            name
+        )));
+    }
+    if name.eq_ignore_ascii_case("anonymous") {
+        return Err(S3Error::InvalidArgument(
+            "The user name 'anonymous' is reserved for unsigned requests".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Admin API view of a trashed object; `None` if it isn't actually in the trash
+fn trash_entry(
+    obj: object::Model,
+    bucket_names: &HashMap<Uuid, String>,
+    retention: Duration,
+) -> Option<TrashEntry> {
+    let deleted_at = obj.deleted_at?;
+    Some(TrashEntry {
+        id: obj.id,
+        bucket: bucket_names
+            .get(&obj.bucket_id)
+            .cloned()
+            .unwrap_or_default(),
+        key: obj.key,
+        size: obj.size,
+        etag: obj.etag,
+        deleted_at,
+        expires_at: deleted_at + retention,
+    })
+}
+
+/// POST /_admin/trash/{id}/restore — Restore a trashed object under its original key
+pub async fn restore_trashed(
+    State(state): State<AppState>,
+    Path(object_id): Path<String>,
+) -> Result<Response, S3Error> {
+    let object_id = Uuid::parse_str(&object_id)
+        .map_err(|_| S3Error::InvalidArgument("Invalid object ID".to_string()))?;
+
+    // Hold the pipeline lock so the key can't be re-created between check and restore
+    let pipeline = state.pipeline.lock().await;
+
+    let trashed = object::Entity::find_by_id(object_id)
+        .filter(object::Column::DeletedAt.is_not_null())
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchKey(format!("Trashed object '{}' not found", object_id)))?;
+
+    // Never overwrite live data: the key must have been left free
+    let existing = object::Entity::find()
+        .filter(object::Column::BucketId.eq(trashed.bucket_id))
+        .filter(object::Column::Key.eq(&trashed.key))
+        .filter(object::Column::VersionId.eq(&trashed.version_id))
+        .filter(object::Column::DeletedAt.is_null())
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    if existing.is_some() {
+        return Err(S3Error::InvalidRequest(format!(
+            "Object '{}' already exists; delete it before restoring",
+            trashed.key
+        )));
+    }
+
+    let restored = pipeline
+        .restore(&trashed)
+        .await
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok(Json(serde_json::json!({
+        "id": restored.id,
+        "key": restored.key,
+        "version_id": restored.version_id,
+    }))
+    .into_response())
+}
+
+/// A user and their access keys (without secrets), as returned by the admin API
+#[derive(Debug, Serialize)]
+pub struct UserEntry {
+    pub id: Uuid,
+    pub name: String,
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub access_keys: Vec<AccessKeyEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccessKeyEntry {
+    pub access_key_id: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A newly issued key pair; the only time the secret is returned
+#[derive(Debug, Serialize)]
+pub struct NewAccessKey {
+    pub user: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// GET /_admin/users — List users and their access keys
+pub async fn list_users(State(state): State<AppState>) -> Result<Response, S3Error> {
+    let users = user::Entity::find()
+        .find_with_related(access_key::Entity)
+        .order_by_asc(user::Column::Name)
+        .all(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    let entries: Vec<UserEntry> = users
+        .into_iter()
+        .map(|(user, keys)| UserEntry {
+            id: user.id,
+            name: user.name,
+            disabled: user.disabled,
+            created_at: user.created_at,
+            access_keys: keys
+                .into_iter()
+                .map(|k| AccessKeyEntry {
+                    access_key_id: k.access_key_id,
+                    status: k.status,
+                    created_at: k.created_at,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(entries).into_response())
+}
+
+/// POST /_admin/users/{name} — Create a user with a first access key
+pub async fn create_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    check_user_name(&name)?;
+
+    let existing = user::Entity::find()
+        .filter(user::Column::Name.eq(&name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    if existing.is_some() {
+        return Err(S3Error::EntityAlreadyExists(format!(
+            "User '{}' already exists",
+            name
+        )));
+    }
+
+    let new_user = user::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        name: Set(name),
+        disabled: Set(false),
+        created_at: Set(Utc::now()),
+        policy: Set(None),
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!("User '{}' created", new_user.name);
+    issue_access_key(&state, &new_user).await
+}
+
+/// POST /_admin/users/{name}/disable — Block all of a user's keys
+pub async fn disable_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    set_user_disabled(&state, &name, true).await
+}
+
+/// POST /_admin/users/{name}/enable — Re-enable a disabled user
+pub async fn enable_user(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    set_user_disabled(&state, &name, false).await
+}
+
+/// POST /_admin/users/{name}/keys — Issue another access key for a user
+pub async fn create_access_key(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    let user = find_user(&state, &name).await?;
+    issue_access_key(&state, &user).await
+}
+
+/// GET /_admin/users/{name}/policy — Return a user's identity policy
+pub async fn get_user_policy(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    let user = find_user(&state, &name).await?;
+    let policy = user
+        .policy
+        .ok_or_else(|| S3Error::NoSuchEntity(format!("User '{}' has no policy attached", name)))?;
+    Ok(Json(policy).into_response())
+}
+
+/// PUT /_admin/users/{name}/policy — Attach (or replace) a user's identity policy
+pub async fn put_user_policy(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    Policy::parse(&body, None)?;
+    let document: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| S3Error::MalformedPolicy(format!("Invalid policy document: {}", e)))?;
+    set_user_policy(&state, &name, Some(document)).await
+}
+
+/// DELETE /_admin/users/{name}/policy — Detach a user's identity policy
+pub async fn delete_user_policy(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, S3Error> {
+    set_user_policy(&state, &name, None).await
+}
+
+/// POST /_admin/keys/{id}/disable — Stop a key from authenticating
+pub async fn disable_access_key(
+    State(state): State<AppState>,
+    Path(access_key_id): Path<String>,
+) -> Result<Response, S3Error> {
+    set_access_key_status(&state, &access_key_id, KEY_INACTIVE).await
+}
+
+/// POST /_admin/keys/{id}/enable — Re-activate a disabled key
+pub async fn enable_access_key(
+    State(state): State<AppState>,
+    Path(access_key_id): Path<String>,
+) -> Result<Response, S3Error> {
+    set_access_key_status(&state, &access_key_id, KEY_ACTIVE).await
+}
+
+/// POST /_admin/keys/{id}/rotate — Replace a key's secret; the old secret stops working immediately
+pub async fn rotate_access_key(
+    State(state): State<AppState>,
+    Path(access_key_id): Path<String>,
+) -> Result<Response, S3Error> {
+    let key = find_access_key(&state, &access_key_id).await?;
+    let user_id = key.user_id;
+    let secret = identity::generate_secret_access_key();
+
+    let mut active: access_key::ActiveModel = key.into();
+    active.secret_access_key = Set(secret.clone());
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    let user = user::Entity::find_by_id(user_id)
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchEntity("User not found".to_string()))?;
+
+    tracing::info!("Access key '{}' rotated", access_key_id);
+    Ok(Json(NewAccessKey {
+        user: user.name,
+        access_key_id,
+        secret_access_key: secret,
+    })
+    .into_response())
+}
+
+/// DELETE /_admin/keys/{id} — Delete an access key
+pub async fn delete_access_key(
+    State(state): State<AppState>,
+    Path(access_key_id): Path<String>,
+) -> Result<Response, S3Error> {
+    let result = access_key::Entity::delete_by_id(access_key_id.clone())
+        .exec(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    if result.rows_affected == 0 {
+        return Err(no_such_key(&access_key_id));
+    }
+
+    tracing::info!("Access key '{}' deleted", access_key_id);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn issue_access_key(state: &AppState, user: &user::Model) -> Result<Response, S3Error> {
+    let key = access_key::ActiveModel {
+        access_key_id: Set(identity::generate_access_key_id()),
+        user_id: Set(user.id),
+        secret_access_key: Set(identity::generate_secret_access_key()),
+        status: Set(KEY_ACTIVE.to_string()),
+        created_at: Set(Utc::now()),
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!(
+        "Access key '{}' issued to '{}'",
+        key.access_key_id,
+        user.name
+    );
+    Ok(Json(NewAccessKey {
+        user: user.name.clone(),
+        access_key_id: key.access_key_id,
+        secret_access_key: key.secret_access_key,
+    })
+    .into_response())
+}
+
+async fn set_user_disabled(
+    state: &AppState,
+    name: &str,
+    disabled: bool,
+) -> Result<Response, S3Error> {
+    let mut active: user::ActiveModel = find_user(state, name).await?.into();
+    active.disabled = Set(disabled);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn set_user_policy(
+    state: &AppState,
+    name: &str,
+    policy: Option<serde_json::Value>,
+) -> Result<Response, S3Error> {
+    let mut active: user::ActiveModel = find_user(state, name).await?.into();
+    active.policy = Set(policy);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn set_access_key_status(
+    state: &AppState,
+    access_key_id: &str,
+    status: &str,
+) -> Result<Response, S3Error> {
+    let mut active: access_key::ActiveModel = find_access_key(state, access_key_id).await?.into();
+    active.status = Set(status.to_string());
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn find_user(state: &AppState, name: &str) -> Result<user::Model, S3Error> {
+    user::Entity::find()
+        .filter(user::Column::Name.eq(name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchEntity(format!("User '{}' not found", name)))
+}
+
+async fn find_access_key(
+    state: &AppState,
+    access_key_id: &str,
+) -> Result<access_key::Model, S3Error> {
+    access_key::Entity::find_by_id(access_key_id)
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| no_such_key(access_key_id))
+}
+
+fn no_such_key(access_key_id: &str) -> S3Error {
+    S3Error::NoSuchEntity(format!("Access key '{}' not found", access_key_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_entry() {
+        let deleted_at = Utc::now();
+        let mut obj = object::Model {
+            id: Uuid::new_v4(),
+            bucket_id: Uuid::new_v4(),
+            key: "a".to_string(),
+            size: 3,
+            etag: "\"etag\"".to_string(),
+            content_type: String::new(),
+            metadata: None,
+            chunk_count: 1,
+            created_at: deleted_at,
+            updated_at: deleted_at,
+            version_id: "null".to_string(),
+            is_latest: false,
+            is_delete_marker: false,
+            deleted_at: Some(deleted_at),
+            tags: None,
+            checksum_algorithm: None,
+            checksum: None,
+            headers: None,
+            sse_customer_key_md5: None,
+        };
+        let bucket_names = HashMap::from([(obj.bucket_id, "photos".to_string())]);
+
+        let entry = trash_entry(obj.clone(), &bucket_names, Duration::hours(72)).unwrap();
+        assert_eq!(entry.bucket, "photos");
+        assert_eq!(entry.expires_at, deleted_at + Duration::hours(72));
+
+        obj.deleted_at = None;
+        assert!(trash_entry(obj, &bucket_names, Duration::hours(72)).is_none());
+    }
+
+    #[test]
+    fn test_check_user_name() {
+        assert!(check_user_name("restore-job").is_ok());
+        assert!(check_user_name("ci+deploy@example.com").is_ok());
+        assert!(check_user_name("").is_err());
+        assert!(check_user_name(&"a".repeat(65)).is_err());
+        assert!(check_user_name("a/b").is_err());
+        assert!(check_user_name("anonymous").is_err());
+        assert!(check_user_name("Anonymous").is_err());
+    }
+}
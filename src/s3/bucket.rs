@@ -3,6 +3,7 @@ use axum::{
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
 use chrono::Utc;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
@@ -10,14 +11,22 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::db::entities::{bucket, object};
+use crate::s3::acl;
+use crate::s3::auth;
 use crate::s3::error::S3Error;
+use crate::s3::identity::Principal;
+use crate::s3::listing;
+use crate::s3::policy::Policy;
 use crate::s3::xml;
+use crate::storage::lifecycle;
 use crate::AppState;
 
 /// PUT /{bucket} — Create bucket
 pub async fn create_bucket(
     State(state): State<AppState>,
     Path(bucket_name): Path<String>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     body: axum::body::Bytes,
 ) -> Result<Response, S3Error> {
     tracing::info!("Creating bucket: {}", bucket_name);
@@ -35,13 +44,21 @@ pub async fn create_bucket(
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
 
-    if existing.is_some() {
+    if let Some(existing) = existing {
+        if existing.owner_id != principal.id {
+            return Err(S3Error::BucketAlreadyExists(format!(
+                "Bucket '{}' already exists and is owned by another user",
+                bucket_name
+            )));
+        }
         return Err(S3Error::BucketAlreadyOwnedByYou(format!(
             "Bucket '{}' already exists",
             bucket_name
         )));
     }
 
+    let acl = acl::canned_acl_from_header(&headers)?.unwrap_or(acl::ACL_PRIVATE);
+
     // Parse optional location constraint from body
     let region = if !body.is_empty() {
         let body_str = std::str::from_utf8(&body).unwrap_or("");
@@ -56,9 +73,11 @@ pub async fn create_bucket(
     let new_bucket = bucket::ActiveModel {
         id: Set(Uuid::new_v4()),
         name: Set(bucket_name.clone()),
-        owner_id: Set(state.config.s3.access_key_id.clone()),
+        owner_id: Set(principal.id.clone()),
         region: Set(region),
         created_at: Set(Utc::now()),
+        acl: Set(acl.to_string()),
+        ..Default::default()
     };
 
     new_bucket
@@ -83,9 +102,10 @@ pub async fn delete_bucket(
         .map_err(|e| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    // Check if bucket is empty
+    // Check if bucket is empty (trashed objects don't count)
     let object_count = object::Entity::find()
         .filter(object::Column::BucketId.eq(bucket.id))
+        .filter(object::Column::DeletedAt.is_null())
         .count(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
@@ -97,6 +117,23 @@ pub async fn delete_bucket(
         )));
     }
 
+    // Purge the bucket's trash first so its drafts are released, not orphaned
+    let trashed: Vec<Uuid> = object::Entity::find()
+        .filter(object::Column::BucketId.eq(bucket.id))
+        .all(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .into_iter()
+        .map(|o| o.id)
+        .collect();
+    if !trashed.is_empty() {
+        let pipeline = state.pipeline.lock().await;
+        pipeline
+            .purge(&trashed)
+            .await
+            .map_err(|e| S3Error::InternalError(e.to_string()))?;
+    }
+
     bucket::Entity::delete_by_id(bucket.id)
         .exec(&state.db)
         .await
@@ -111,23 +148,27 @@ pub async fn head_bucket(
     State(state): State<AppState>,
     Path(bucket_name): Path<String>,
 ) -> Result<Response, S3Error> {
-    let _bucket = bucket::Entity::find()
+    let bucket = bucket::Entity::find()
         .filter(bucket::Column::Name.eq(&bucket_name))
         .one(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    Ok((
-        StatusCode::OK,
-        [("x-amz-bucket-region", state.config.s3.region.as_str())],
-    )
-        .into_response())
+    Ok((StatusCode::OK, [("x-amz-bucket-region", bucket.region)]).into_response())
 }
 
 /// GET / — List all buckets
-pub async fn list_buckets(State(state): State<AppState>) -> Result<Response, S3Error> {
-    let buckets = bucket::Entity::find()
+pub async fn list_buckets(
+    State(state): State<AppState>,
+    Extension(principal): Extension<Principal>,
+) -> Result<Response, S3Error> {
+    // The root credential sees every bucket, users only their own
+    let mut query = bucket::Entity::find();
+    if !principal.is_root {
+        query = query.filter(bucket::Column::OwnerId.eq(&principal.id));
+    }
+    let buckets = query
         .all(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
@@ -143,8 +184,8 @@ pub async fn list_buckets(State(state): State<AppState>) -> Result<Response, S3E
                 .collect(),
         },
         owner: xml::Owner {
-            id: state.config.s3.access_key_id.clone(),
-            display_name: state.config.s3.access_key_id.clone(),
+            id: principal.id,
+            display_name: principal.display_name,
         },
     };
 
@@ -174,6 +215,13 @@ pub struct ListObjectsQuery {
     pub fetch_owner: Option<String>,
     #[serde(rename = "encoding-type")]
     pub encoding_type: Option<String>,
+    /// ListObjects (V1) resume point
+    pub marker: Option<String>,
+    /// ListObjectVersions resume point
+    #[serde(rename = "key-marker")]
+    pub key_marker: Option<String>,
+    #[serde(rename = "version-id-marker")]
+    pub version_id_marker: Option<String>,
 }
 
 /// GET /{bucket}?list-type=2 — List objects in bucket
@@ -190,64 +238,209 @@ pub async fn list_objects_v2(
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
     let prefix = params.prefix.unwrap_or_default();
-    let delimiter = params.delimiter.clone();
-    let max_keys = params.max_keys.unwrap_or(1000);
+    let delimiter = params.delimiter.filter(|d| !d.is_empty());
+    let max_keys = parse_max_keys(params.max_keys)?;
+    let url_encode = parse_encoding_type(params.encoding_type.as_deref())?;
+
+    // The continuation token takes precedence over start-after
+    let marker = match params.continuation_token {
+        Some(ref token) => Some(listing::decode_continuation_token(token).ok_or_else(|| {
+            S3Error::InvalidArgument("The continuation token provided is incorrect".to_string())
+        })?),
+        None => params.start_after.clone(),
+    };
 
-    // Query objects with prefix filter
-    let mut query = object::Entity::find().filter(object::Column::BucketId.eq(bucket.id));
+    let page = listing::list_objects(
+        &state.db,
+        bucket.id,
+        &prefix,
+        delimiter.as_deref(),
+        marker.as_deref(),
+        max_keys as usize,
+    )
+    .await
+    .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    if !prefix.is_empty() {
-        query = query.filter(object::Column::Key.starts_with(&prefix));
-    }
+    let encode = |s: String| listing::encode_key(s, url_encode);
+    let result = xml::ListBucketResult {
+        name: bucket_name,
+        prefix: encode(prefix),
+        delimiter: delimiter.map(encode),
+        max_keys,
+        is_truncated: page.is_truncated,
+        key_count: (page.objects.len() + page.common_prefixes.len()) as i32,
+        contents: list_contents(&page, url_encode),
+        common_prefixes: list_common_prefixes(&page, url_encode),
+        continuation_token: params.continuation_token,
+        next_continuation_token: page
+            .next_marker
+            .as_deref()
+            .map(listing::encode_continuation_token),
+        start_after: params.start_after.map(encode),
+        encoding_type: params.encoding_type,
+    };
 
-    let objects = query
-        .all(&state.db)
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// GET /{bucket} — List objects in bucket (V1, marker-based)
+pub async fn list_objects(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    Query(params): Query<ListObjectsQuery>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
         .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+        .map_err(|e| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    // Apply delimiter logic for common prefixes
-    let mut contents = Vec::new();
-    let mut common_prefixes_set = std::collections::BTreeSet::new();
-
-    for obj in &objects {
-        if let Some(ref delim) = delimiter {
-            let after_prefix = &obj.key[prefix.len()..];
-            if let Some(pos) = after_prefix.find(delim.as_str()) {
-                // This key has a delimiter after the prefix — add as common prefix
-                let common_prefix = format!("{}{}", prefix, &after_prefix[..=pos]);
-                common_prefixes_set.insert(common_prefix);
-                continue;
-            }
+    let prefix = params.prefix.unwrap_or_default();
+    let delimiter = params.delimiter.filter(|d| !d.is_empty());
+    let max_keys = parse_max_keys(params.max_keys)?;
+    let url_encode = parse_encoding_type(params.encoding_type.as_deref())?;
+
+    let page = listing::list_objects(
+        &state.db,
+        bucket.id,
+        &prefix,
+        delimiter.as_deref(),
+        params.marker.as_deref(),
+        max_keys as usize,
+    )
+    .await
+    .map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let encode = |s: String| listing::encode_key(s, url_encode);
+    let result = xml::ListBucketResultV1 {
+        name: bucket_name,
+        prefix: encode(prefix),
+        marker: encode(params.marker.unwrap_or_default()),
+        next_marker: page.next_marker.clone().map(encode),
+        delimiter: delimiter.map(encode),
+        max_keys,
+        encoding_type: params.encoding_type,
+        is_truncated: page.is_truncated,
+        contents: list_contents(&page, url_encode),
+        common_prefixes: list_common_prefixes(&page, url_encode),
+    };
+
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// GET /{bucket}?versions — List object versions and delete markers
+pub async fn list_object_versions(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    Query(params): Query<ListObjectsQuery>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let prefix = params.prefix.unwrap_or_default();
+    let delimiter = params.delimiter.filter(|d| !d.is_empty());
+    let max_keys = parse_max_keys(params.max_keys)?;
+    let url_encode = parse_encoding_type(params.encoding_type.as_deref())?;
+    let key_marker = params.key_marker.filter(|m| !m.is_empty());
+    let version_id_marker = params.version_id_marker.filter(|m| !m.is_empty());
+
+    // Resume after a specific version of the key marker
+    let version_marker = match (&key_marker, &version_id_marker) {
+        (None, Some(_)) => {
+            return Err(S3Error::InvalidArgument(
+                "A version-id marker cannot be specified without a key marker.".to_string(),
+            ));
         }
+        (Some(key), Some(version_id)) => Some(
+            object::Entity::find()
+                .filter(object::Column::BucketId.eq(bucket.id))
+                .filter(object::Column::Key.eq(key))
+                .filter(object::Column::VersionId.eq(version_id))
+                .filter(object::Column::DeletedAt.is_null())
+                .one(&state.db)
+                .await
+                .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+                .ok_or_else(|| {
+                    S3Error::InvalidArgument("Invalid version id specified".to_string())
+                })?,
+        ),
+        _ => None,
+    };
 
-        contents.push(xml::ObjectInfo {
-            key: obj.key.clone(),
-            last_modified: obj.updated_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
-            etag: obj.etag.clone(),
-            size: obj.size,
-            storage_class: "STANDARD".to_string(),
-        });
+    let page = listing::list_object_versions(
+        &state.db,
+        bucket.id,
+        &prefix,
+        delimiter.as_deref(),
+        key_marker.as_deref(),
+        version_marker,
+        max_keys as usize,
+    )
+    .await
+    .map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let encode = |s: String| listing::encode_key(s, url_encode);
+    let mut versions = Vec::new();
+    let mut delete_markers = Vec::new();
+    for obj in page.versions {
+        let last_modified = obj.updated_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        if obj.is_delete_marker {
+            delete_markers.push(xml::DeleteMarkerEntry {
+                key: encode(obj.key),
+                version_id: obj.version_id,
+                is_latest: obj.is_latest,
+                last_modified,
+            });
+        } else {
+            versions.push(xml::ObjectVersion {
+                key: encode(obj.key),
+                version_id: obj.version_id,
+                is_latest: obj.is_latest,
+                last_modified,
+                etag: obj.etag,
+                size: obj.size,
+                storage_class: "STANDARD".to_string(),
+            });
+        }
     }
 
-    // Truncate to max_keys
-    let is_truncated = contents.len() as i32 > max_keys;
-    let key_count = std::cmp::min(contents.len() as i32, max_keys);
-    contents.truncate(max_keys as usize);
-
-    let result = xml::ListBucketResult {
+    let result = xml::ListVersionsResult {
         name: bucket_name,
-        prefix,
-        delimiter,
+        prefix: encode(prefix),
+        key_marker: encode(key_marker.unwrap_or_default()),
+        version_id_marker: version_id_marker.unwrap_or_default(),
+        next_key_marker: page.next_key_marker.map(encode),
+        next_version_id_marker: page.next_version_id_marker,
+        delimiter: delimiter.map(encode),
         max_keys,
-        is_truncated,
-        key_count,
-        contents,
-        common_prefixes: common_prefixes_set
+        encoding_type: params.encoding_type,
+        is_truncated: page.is_truncated,
+        versions,
+        delete_markers,
+        common_prefixes: page
+            .common_prefixes
             .into_iter()
-            .map(|p| xml::CommonPrefix { prefix: p })
+            .map(|p| xml::CommonPrefix { prefix: encode(p) })
             .collect(),
-        continuation_token: params.continuation_token,
-        next_continuation_token: None,
     };
 
     let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
@@ -259,3 +452,470 @@ pub async fn list_objects_v2(
     )
         .into_response())
 }
+
+/// Validate `max-keys`, capping it at the S3 limit of 1000
+fn parse_max_keys(max_keys: Option<i32>) -> Result<i32, S3Error> {
+    match max_keys {
+        Some(n) if n < 0 => Err(S3Error::InvalidArgument(
+            "max-keys must be a non-negative integer".to_string(),
+        )),
+        Some(n) => Ok(n.min(1000)),
+        None => Ok(1000),
+    }
+}
+
+/// Validate `encoding-type`; returns whether keys should be URL-encoded
+fn parse_encoding_type(encoding_type: Option<&str>) -> Result<bool, S3Error> {
+    match encoding_type {
+        None => Ok(false),
+        Some("url") => Ok(true),
+        Some(_) => Err(S3Error::InvalidArgument(
+            "Invalid Encoding Method specified in Request".to_string(),
+        )),
+    }
+}
+
+fn list_contents(page: &listing::ListPage, url_encode: bool) -> Vec<xml::ObjectInfo> {
+    page.objects
+        .iter()
+        .map(|obj| xml::ObjectInfo {
+            key: listing::encode_key(obj.key.clone(), url_encode),
+            last_modified: obj.updated_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            etag: obj.etag.clone(),
+            size: obj.size,
+            storage_class: "STANDARD".to_string(),
+        })
+        .collect()
+}
+
+fn list_common_prefixes(page: &listing::ListPage, url_encode: bool) -> Vec<xml::CommonPrefix> {
+    page.common_prefixes
+        .iter()
+        .map(|p| xml::CommonPrefix {
+            prefix: listing::encode_key(p.clone(), url_encode),
+        })
+        .collect()
+}
+
+/// Every bucket subresource S3 defines. A request naming one that isn't
+/// dispatched for its method must not fall through to ListObjects,
+/// CreateBucket or DeleteBucket.
+pub const BUCKET_SUBRESOURCES: &[&str] = &[
+    "accelerate",
+    "acl",
+    "analytics",
+    "cors",
+    "delete",
+    "encryption",
+    "intelligent-tiering",
+    "inventory",
+    "lifecycle",
+    "location",
+    "logging",
+    "metrics",
+    "notification",
+    "object-lock",
+    "ownershipControls",
+    "policy",
+    "policyStatus",
+    "publicAccessBlock",
+    "replication",
+    "requestPayment",
+    "tagging",
+    "uploads",
+    "versioning",
+    "versions",
+    "website",
+];
+
+/// Bucket sub-resource selectors (e.g. `?lifecycle`)
+#[derive(Debug, Deserialize)]
+pub struct BucketQuery {
+    pub location: Option<String>,
+    pub lifecycle: Option<String>,
+    pub delete: Option<String>,
+    pub versioning: Option<String>,
+    pub versions: Option<String>,
+    pub policy: Option<String>,
+    pub acl: Option<String>,
+    pub cors: Option<String>,
+}
+
+/// NotImplemented if the query names a bucket subresource; called once a
+/// dispatcher has ruled out all the subresources it handles
+pub fn reject_subresources(query: Option<&str>) -> Result<(), S3Error> {
+    let params = auth::query_params(query.unwrap_or(""));
+    match params
+        .iter()
+        .find(|(name, _)| BUCKET_SUBRESOURCES.contains(&name.as_str()))
+    {
+        Some((name, _)) => Err(S3Error::NotImplemented(format!(
+            "The bucket subresource '{}' is not supported for this request",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// GET /{bucket}?location — Return the region the bucket was created in
+pub async fn get_bucket_location(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let location = xml::LocationConstraint {
+        region: location_constraint(bucket.region),
+    };
+    let xml_body = xml::to_xml(&location).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// S3 reports us-east-1 as an empty constraint, and SDKs expect that
+fn location_constraint(region: String) -> String {
+    if region == "us-east-1" {
+        String::new()
+    } else {
+        region
+    }
+}
+
+/// PUT /{bucket}?lifecycle — Store lifecycle configuration
+pub async fn put_bucket_lifecycle(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let body_str = std::str::from_utf8(&body)
+        .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+
+    let config: xml::LifecycleConfiguration = xml::from_xml(body_str).map_err(|e| {
+        S3Error::MalformedXML(format!("Failed to parse LifecycleConfiguration XML: {}", e))
+    })?;
+
+    if config.rules.is_empty() || config.rules.len() > 1000 {
+        return Err(S3Error::MalformedXML(
+            "Lifecycle configuration must contain between 1 and 1000 rules".to_string(),
+        ));
+    }
+
+    for rule in &config.rules {
+        if rule.status != "Enabled" && rule.status != "Disabled" {
+            return Err(S3Error::MalformedXML(format!(
+                "Invalid lifecycle rule status '{}'",
+                rule.status
+            )));
+        }
+        if rule.expiration.is_none()
+            && rule.noncurrent_version_expiration.is_none()
+            && rule.abort_incomplete_multipart_upload.is_none()
+        {
+            return Err(S3Error::MalformedXML(
+                "Each lifecycle rule must specify at least one action".to_string(),
+            ));
+        }
+        if let Some(ref expiration) = rule.expiration {
+            let specified = [
+                expiration.days.is_some(),
+                expiration.date.is_some(),
+                expiration.expired_object_delete_marker.is_some(),
+            ];
+            if specified.iter().filter(|s| **s).count() != 1 {
+                return Err(S3Error::MalformedXML(
+                    "Expiration must specify exactly one of Days, Date or \
+                     ExpiredObjectDeleteMarker"
+                        .to_string(),
+                ));
+            }
+            if expiration.days.is_some_and(|days| days <= 0) {
+                return Err(S3Error::InvalidArgument(
+                    "Expiration Days must be a positive integer".to_string(),
+                ));
+            }
+            if let Some(ref date) = expiration.date {
+                if lifecycle::parse_date(date).is_none() {
+                    return Err(S3Error::InvalidArgument(format!(
+                        "Invalid Expiration Date '{}'",
+                        date
+                    )));
+                }
+            }
+        }
+        if let Some(ref noncurrent) = rule.noncurrent_version_expiration {
+            if noncurrent.noncurrent_days <= 0 {
+                return Err(S3Error::InvalidArgument(
+                    "NoncurrentDays must be a positive integer".to_string(),
+                ));
+            }
+        }
+        if let Some(ref abort) = rule.abort_incomplete_multipart_upload {
+            if abort.days_after_initiation <= 0 {
+                return Err(S3Error::InvalidArgument(
+                    "DaysAfterInitiation must be a positive integer".to_string(),
+                ));
+            }
+            if rule.has_tag_filter() {
+                return Err(S3Error::InvalidRequest(
+                    "AbortIncompleteMultipartUpload cannot be specified with Tags".to_string(),
+                ));
+            }
+        }
+    }
+
+    let lifecycle_json =
+        serde_json::to_value(&config).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.lifecycle = Set(Some(lifecycle_json));
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!(
+        "Lifecycle configuration for bucket '{}' updated ({} rules)",
+        bucket_name,
+        config.rules.len()
+    );
+    Ok(StatusCode::OK.into_response())
+}
+
+/// GET /{bucket}?lifecycle — Return lifecycle configuration
+pub async fn get_bucket_lifecycle(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let config: xml::LifecycleConfiguration = bucket
+        .lifecycle
+        .and_then(|v| serde_json::from_value(v).ok())
+        .ok_or_else(|| {
+            S3Error::NoSuchLifecycleConfiguration(
+                "The lifecycle configuration does not exist".to_string(),
+            )
+        })?;
+
+    let xml_body = xml::to_xml(&config).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// DELETE /{bucket}?lifecycle — Remove lifecycle configuration
+pub async fn delete_bucket_lifecycle(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.lifecycle = Set(None);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// PUT /{bucket}?policy — Store the bucket policy
+pub async fn put_bucket_policy(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    // Validate, but store the document as given so GetBucketPolicy returns it
+    Policy::parse(&body, Some(&bucket_name))?;
+    let document: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| S3Error::MalformedPolicy(format!("Invalid policy document: {}", e)))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.policy = Set(Some(document));
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// GET /{bucket}?policy — Return the bucket policy
+pub async fn get_bucket_policy(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let policy = bucket.policy.ok_or_else(|| {
+        S3Error::NoSuchBucketPolicy("The bucket policy does not exist".to_string())
+    })?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/json")],
+        policy.to_string(),
+    )
+        .into_response())
+}
+
+/// DELETE /{bucket}?policy — Remove the bucket policy
+pub async fn delete_bucket_policy(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.policy = Set(None);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// PUT /{bucket}?versioning — Enable or suspend versioning
+pub async fn put_bucket_versioning(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let body_str = std::str::from_utf8(&body)
+        .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+
+    let config: xml::VersioningConfiguration = xml::from_xml(body_str).map_err(|e| {
+        S3Error::MalformedXML(format!(
+            "Failed to parse VersioningConfiguration XML: {}",
+            e
+        ))
+    })?;
+
+    let status = match config.status.as_deref() {
+        Some(status @ ("Enabled" | "Suspended")) => status.to_string(),
+        _ => {
+            return Err(S3Error::MalformedXML(
+                "Versioning status must be Enabled or Suspended".to_string(),
+            ));
+        }
+    };
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.versioning = Set(Some(status.clone()));
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!("Versioning for bucket '{}' set to {}", bucket_name, status);
+    Ok(StatusCode::OK.into_response())
+}
+
+/// GET /{bucket}?versioning — Return versioning state
+pub async fn get_bucket_versioning(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let config = xml::VersioningConfiguration {
+        status: bucket.versioning,
+    };
+
+    let xml_body = xml::to_xml(&config).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_subresources() {
+        assert!(matches!(
+            reject_subresources(Some("tagging")),
+            Err(S3Error::NotImplemented(_))
+        ));
+        assert!(matches!(
+            reject_subresources(Some("prefix=a&uploads")),
+            Err(S3Error::NotImplemented(_))
+        ));
+        assert!(reject_subresources(Some("prefix=a&list-type=2")).is_ok());
+        assert!(reject_subresources(Some("")).is_ok());
+        assert!(reject_subresources(None).is_ok());
+    }
+
+    #[test]
+    fn test_location_constraint() {
+        assert_eq!(location_constraint("us-east-1".to_string()), "");
+        assert_eq!(location_constraint("eu-west-1".to_string()), "eu-west-1");
+    }
+}
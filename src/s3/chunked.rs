@@ -0,0 +1,217 @@
+use crate::s3::auth::compute_signature;
+use crate::s3::error::S3Error;
+use crate::storage::hasher;
+
+/// SHA256 of the empty string, part of every chunk's string to sign
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// `x-amz-content-sha256` values announcing an aws-chunked body
+pub const STREAMING_SIGNED: &str = "STREAMING-AWS4-HMAC-SHA256-PAYLOAD";
+pub const STREAMING_SIGNED_TRAILER: &str = "STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER";
+pub const STREAMING_UNSIGNED_TRAILER: &str = "STREAMING-UNSIGNED-PAYLOAD-TRAILER";
+
+/// Everything needed to check chunk signatures: each chunk is signed with the
+/// request's signing key, chained from the seed (Authorization header) signature
+pub struct ChunkSigner {
+    pub signing_key: Vec<u8>,
+    pub amz_date: String,
+    pub scope: String,
+    pub seed_signature: String,
+}
+
+/// A decoded aws-chunked body
+#[derive(Debug)]
+pub struct DecodedBody {
+    pub data: Vec<u8>,
+    /// Trailing headers (e.g. `x-amz-checksum-crc32`), names lowercased
+    pub trailers: Vec<(String, String)>,
+}
+
+/// Decode an aws-chunked body, verifying every chunk signature (and the
+/// trailer signature, if there are trailers) when `signer` is given
+pub fn decode(body: &[u8], signer: Option<&ChunkSigner>) -> Result<DecodedBody, S3Error> {
+    let mut reader = Reader { body, pos: 0 };
+    let mut previous_signature = signer.map(|s| s.seed_signature.clone());
+    let mut data = Vec::new();
+
+    loop {
+        let header = reader.line()?;
+        let (size, signature) = match header.split_once(';') {
+            Some((size, ext)) => (size, ext.strip_prefix("chunk-signature=")),
+            None => (header, None),
+        };
+        let size =
+            usize::from_str_radix(size.trim(), 16).map_err(|_| malformed("invalid chunk size"))?;
+        let chunk = reader.take(size)?;
+
+        if let (Some(signer), Some(previous)) = (signer, previous_signature.as_mut()) {
+            let signature = signature.ok_or_else(|| malformed("missing chunk signature"))?;
+            let string_to_sign = format!(
+                "AWS4-HMAC-SHA256-PAYLOAD\n{}\n{}\n{}\n{}\n{}",
+                signer.amz_date,
+                signer.scope,
+                previous,
+                EMPTY_SHA256,
+                hasher::compute_sha256(chunk)
+            );
+            if compute_signature(&signer.signing_key, &string_to_sign) != signature {
+                return Err(chunk_signature_mismatch());
+            }
+            *previous = signature.to_string();
+        }
+
+        if size == 0 {
+            break;
+        }
+        data.extend_from_slice(chunk);
+        if !reader.line()?.is_empty() {
+            return Err(malformed("chunk data longer than its declared size"));
+        }
+    }
+
+    // The final chunk is followed by optional trailing headers and an empty line
+    let mut trailers = Vec::new();
+    let mut trailer_signature = None;
+    loop {
+        let line = reader.line()?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed("invalid trailing header"))?;
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+        if name == "x-amz-trailer-signature" {
+            trailer_signature = Some(value);
+        } else {
+            trailers.push((name, value));
+        }
+    }
+
+    if let (Some(signer), Some(previous)) = (signer, previous_signature) {
+        if !trailers.is_empty() {
+            let signature =
+                trailer_signature.ok_or_else(|| malformed("missing trailer signature"))?;
+            let canonical: String = trailers
+                .iter()
+                .map(|(name, value)| format!("{}:{}\n", name, value))
+                .collect();
+            let string_to_sign = format!(
+                "AWS4-HMAC-SHA256-TRAILER\n{}\n{}\n{}\n{}",
+                signer.amz_date,
+                signer.scope,
+                previous,
+                hasher::compute_sha256(canonical.as_bytes())
+            );
+            if compute_signature(&signer.signing_key, &string_to_sign) != signature {
+                return Err(chunk_signature_mismatch());
+            }
+        }
+    }
+
+    Ok(DecodedBody { data, trailers })
+}
+
+/// Cursor over the raw body
+struct Reader<'a> {
+    body: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Next CRLF-terminated line, without the CRLF
+    fn line(&mut self) -> Result<&'a str, S3Error> {
+        let rest = &self.body[self.pos..];
+        let end = rest
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| malformed("unexpected end of body"))?;
+        self.pos += end + 2;
+        std::str::from_utf8(&rest[..end]).map_err(|_| malformed("invalid chunk header"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], S3Error> {
+        let rest = &self.body[self.pos..];
+        if rest.len() < len {
+            return Err(malformed("chunk shorter than its declared size"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+}
+
+fn malformed(reason: &str) -> S3Error {
+    S3Error::InvalidRequest(format!("Malformed aws-chunked body: {}", reason))
+}
+
+fn chunk_signature_mismatch() -> S3Error {
+    S3Error::SignatureDoesNotMatch(
+        "The chunk signature we calculated does not match the signature you provided".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::auth::derive_signing_key;
+
+    // Example from the AWS documentation ("Signature Calculations for the
+    // Authorization Header: Transferring Payload in Multiple Chunks")
+    #[test]
+    fn test_decode_signed_chunks() {
+        let signer = ChunkSigner {
+            signing_key: derive_signing_key(
+                "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+                "20130524",
+                "us-east-1",
+                "s3",
+            ),
+            amz_date: "20130524T000000Z".to_string(),
+            scope: "20130524/us-east-1/s3/aws4_request".to_string(),
+            seed_signature: "4f232c4386841ef735655705268965c44a0e4690baa4adea153f7db9fa80a0a9"
+                .to_string(),
+        };
+
+        let mut body = Vec::new();
+        body.extend_from_slice(
+            b"10000;chunk-signature=\
+              ad80c730a21e5b8d04586a2213dd63b9a0e99e0e2307b0ade35a65485a288648\r\n",
+        );
+        body.extend_from_slice(&[b'a'; 65536]);
+        body.extend_from_slice(
+            b"\r\n400;chunk-signature=\
+              0055627c9e194cb4542bae2aa5492e3c1575bbb81b612b7d234b86a503ef5497\r\n",
+        );
+        body.extend_from_slice(&[b'a'; 1024]);
+        body.extend_from_slice(
+            b"\r\n0;chunk-signature=\
+              b6c6ea8a5354eaf15b3cb7646744f4275b71ea724fed81ceb9323e279d449df9\r\n\r\n",
+        );
+
+        let decoded = decode(&body, Some(&signer)).unwrap();
+        assert_eq!(decoded.data, vec![b'a'; 66560]);
+        assert!(decoded.trailers.is_empty());
+
+        // Any change to the data breaks the chain
+        body[200] = b'b';
+        assert!(matches!(
+            decode(&body, Some(&signer)),
+            Err(S3Error::SignatureDoesNotMatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_unsigned_trailer() {
+        let body = b"5\r\nhello\r\n6\r\n world\r\n0\r\nx-amz-checksum-crc32:DUoRhQ==\r\n\r\n";
+        let decoded = decode(body, None).unwrap();
+        assert_eq!(decoded.data, b"hello world");
+        assert_eq!(
+            decoded.trailers,
+            [("x-amz-checksum-crc32".to_string(), "DUoRhQ==".to_string())]
+        );
+
+        assert!(decode(b"5\r\nhel", None).is_err());
+        assert!(decode(b"3\r\nhello\r\n0\r\n\r\n", None).is_err());
+    }
+}
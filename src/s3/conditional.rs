@@ -0,0 +1,154 @@
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+
+use crate::db::entities::object;
+use crate::s3::error::S3Error;
+use crate::s3::object::find_object;
+
+/// Evaluate `If-Match`, `If-Unmodified-Since`, `If-None-Match` and
+/// `If-Modified-Since` for GET/HEAD, in RFC 7232 order: `If-Unmodified-Since`
+/// is ignored when `If-Match` is present, and `If-Modified-Since` when
+/// `If-None-Match` is.
+pub fn check_read_preconditions(headers: &HeaderMap, obj: &object::Model) -> Result<(), S3Error> {
+    if let Some(if_match) = header(headers, "if-match") {
+        if !etag_matches(if_match, &obj.etag) {
+            return Err(precondition_failed());
+        }
+    } else if let Some(since) = header(headers, "if-unmodified-since").and_then(parse_http_date) {
+        if modified_after(obj, since) {
+            return Err(precondition_failed());
+        }
+    }
+
+    if let Some(if_none_match) = header(headers, "if-none-match") {
+        if etag_matches(if_none_match, &obj.etag) {
+            return Err(S3Error::NotModified);
+        }
+    } else if let Some(since) = header(headers, "if-modified-since").and_then(parse_http_date) {
+        if !modified_after(obj, since) {
+            return Err(S3Error::NotModified);
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluate `If-None-Match: *` (create only if absent) and `If-Match` (replace
+/// only the expected ETag) against the current version of `key`. Callers hold
+/// the pipeline lock, so the check and the write that follows are atomic.
+pub async fn check_write_preconditions(
+    db: &DatabaseConnection,
+    headers: &HeaderMap,
+    bucket_id: uuid::Uuid,
+    key: &str,
+) -> Result<(), S3Error> {
+    let if_none_match = header(headers, "if-none-match");
+    let if_match = header(headers, "if-match");
+    if if_none_match.is_none() && if_match.is_none() {
+        return Ok(());
+    }
+    if if_none_match.is_some_and(|v| v.trim() != "*") {
+        return Err(S3Error::NotImplemented(
+            "If-None-Match only supports the value '*' on writes".to_string(),
+        ));
+    }
+
+    let current = match find_object(db, bucket_id, key, None).await {
+        Ok(obj) => Some(obj),
+        Err(S3Error::NoSuchKey(_)) => None,
+        Err(e) => return Err(e),
+    };
+
+    if if_none_match.is_some() && current.is_some() {
+        return Err(precondition_failed());
+    }
+    if let Some(if_match) = if_match {
+        let current =
+            current.ok_or_else(|| S3Error::NoSuchKey(format!("Object '{}' not found", key)))?;
+        if !etag_matches(if_match, &current.etag) {
+            return Err(precondition_failed());
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate `If-Match` for DeleteObject against the version being deleted
+pub async fn check_delete_precondition(
+    db: &DatabaseConnection,
+    headers: &HeaderMap,
+    bucket_id: uuid::Uuid,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<(), S3Error> {
+    let Some(if_match) = header(headers, "if-match") else {
+        return Ok(());
+    };
+    let obj = find_object(db, bucket_id, key, version_id).await?;
+    if !etag_matches(if_match, &obj.etag) {
+        return Err(precondition_failed());
+    }
+    Ok(())
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Match a comma-separated list of entity tags (quoted or not, weak or
+/// strong) or `*` against an object's ETag
+fn etag_matches(condition: &str, etag: &str) -> bool {
+    let etag = etag.trim_matches('"');
+    condition.split(',').map(str::trim).any(|candidate| {
+        candidate == "*"
+            || candidate
+                .strip_prefix("W/")
+                .unwrap_or(candidate)
+                .trim_matches('"')
+                == etag
+    })
+}
+
+/// Parse an HTTP date (`Sun, 06 Nov 1994 08:49:37 GMT`); invalid dates are ignored
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// HTTP dates have one-second resolution, so compare whole seconds
+fn modified_after(obj: &object::Model, since: DateTime<Utc>) -> bool {
+    obj.updated_at.timestamp() > since.timestamp()
+}
+
+fn precondition_failed() -> S3Error {
+    S3Error::PreconditionFailed(
+        "At least one of the pre-conditions you specified did not hold".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"9b2cf535f27731c974343645a3985328\"";
+        assert!(etag_matches("\"9b2cf535f27731c974343645a3985328\"", etag));
+        assert!(etag_matches("9b2cf535f27731c974343645a3985328", etag));
+        assert!(etag_matches(
+            "\"abc\", W/\"9b2cf535f27731c974343645a3985328\"",
+            etag
+        ));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"abc\"", etag));
+        assert!(!etag_matches("", etag));
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let date = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(date.to_rfc3339(), "1994-11-06T08:49:37+00:00");
+        assert!(parse_http_date("yesterday").is_none());
+    }
+}
@@ -0,0 +1,366 @@
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+
+use crate::db::entities::bucket;
+use crate::s3::error::S3Error;
+use crate::s3::xml;
+use crate::AppState;
+
+/// Methods a CORS rule may allow
+const CORS_METHODS: [&str; 5] = ["GET", "PUT", "POST", "DELETE", "HEAD"];
+
+/// PUT /{bucket}?cors — Store the CORS configuration
+pub async fn put_bucket_cors(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let body_str = std::str::from_utf8(&body)
+        .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+    let config: xml::CorsConfiguration = xml::from_xml(body_str).map_err(|e| {
+        S3Error::MalformedXML(format!("Failed to parse CORSConfiguration XML: {}", e))
+    })?;
+    validate_cors(&config)?;
+
+    let cors_json =
+        serde_json::to_value(&config).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.cors = Set(Some(cors_json));
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!(
+        "CORS configuration for bucket '{}' updated ({} rules)",
+        bucket_name,
+        config.rules.len()
+    );
+    Ok(StatusCode::OK.into_response())
+}
+
+/// GET /{bucket}?cors — Return the CORS configuration
+pub async fn get_bucket_cors(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let config = bucket_cors(&bucket).ok_or_else(|| {
+        S3Error::NoSuchCORSConfiguration("The CORS configuration does not exist".to_string())
+    })?;
+
+    let xml_body = xml::to_xml(&config).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// DELETE /{bucket}?cors — Remove the CORS configuration
+pub async fn delete_bucket_cors(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    active.cors = Set(None);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Apply the bucket's CORS rules to browser requests (those with an `Origin`).
+/// Preflight `OPTIONS` requests are answered here, unsigned, without reaching
+/// a handler; other requests get the CORS headers of the first matching rule.
+pub async fn cors_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, S3Error> {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let bucket_name = request
+        .uri()
+        .path()
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .to_string();
+    let Some(origin) = origin else {
+        return Ok(next.run(request).await);
+    };
+    if bucket_name.is_empty() || bucket_name.starts_with('_') {
+        return Ok(next.run(request).await);
+    }
+
+    let preflight_method = request
+        .headers()
+        .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let method = request.method().clone();
+
+    let config = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .and_then(|bucket| bucket_cors(&bucket));
+
+    if method == Method::OPTIONS {
+        let forbidden = || {
+            S3Error::AccessForbidden(
+                "CORSResponse: This CORS request is not allowed. This is usually because the \
+                 evaluation of Origin, request method / Access-Control-Request-Method or \
+                 Access-Control-Request-Headers are not whitelisted by the resource's CORS spec."
+                    .to_string(),
+            )
+        };
+        let preflight_method = preflight_method.ok_or_else(|| {
+            S3Error::InvalidRequest(
+                "Preflight requests must include Access-Control-Request-Method".to_string(),
+            )
+        })?;
+        let requested_headers: Vec<String> = request
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(',')
+                    .map(|h| h.trim().to_ascii_lowercase())
+                    .filter(|h| !h.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let config = config.ok_or_else(forbidden)?;
+        let rule = find_rule(&config, &origin, &preflight_method, &requested_headers)
+            .ok_or_else(forbidden)?;
+
+        let mut response = StatusCode::OK.into_response();
+        let headers = response.headers_mut();
+        set_cors_headers(headers, rule, &origin);
+        if !requested_headers.is_empty() {
+            insert_header(
+                headers,
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                &requested_headers.join(", "),
+            );
+        }
+        if let Some(max_age) = rule.max_age_seconds {
+            insert_header(
+                headers,
+                header::ACCESS_CONTROL_MAX_AGE,
+                &max_age.to_string(),
+            );
+        }
+        return Ok(response);
+    }
+
+    let mut response = next.run(request).await;
+    if let Some(rule) = config
+        .as_ref()
+        .and_then(|config| find_rule(config, &origin, method.as_str(), &[]))
+    {
+        let headers = response.headers_mut();
+        set_cors_headers(headers, rule, &origin);
+        if !rule.expose_headers.is_empty() {
+            insert_header(
+                headers,
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                &rule.expose_headers.join(", "),
+            );
+        }
+    }
+    Ok(response)
+}
+
+/// The bucket's stored CORS configuration, if any
+fn bucket_cors(bucket: &bucket::Model) -> Option<xml::CorsConfiguration> {
+    bucket
+        .cors
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Reject configurations S3 would reject
+fn validate_cors(config: &xml::CorsConfiguration) -> Result<(), S3Error> {
+    if config.rules.is_empty() || config.rules.len() > 100 {
+        return Err(S3Error::MalformedXML(
+            "CORS configuration must contain between 1 and 100 rules".to_string(),
+        ));
+    }
+    for rule in &config.rules {
+        if rule.allowed_origins.is_empty() || rule.allowed_methods.is_empty() {
+            return Err(S3Error::MalformedXML(
+                "Each CORS rule must specify at least one AllowedOrigin and AllowedMethod"
+                    .to_string(),
+            ));
+        }
+        if let Some(method) = rule
+            .allowed_methods
+            .iter()
+            .find(|m| !CORS_METHODS.contains(&m.as_str()))
+        {
+            return Err(S3Error::InvalidRequest(format!(
+                "Found unsupported HTTP method in CORS config. Unsupported method is {}",
+                method
+            )));
+        }
+        let patterns = rule.allowed_origins.iter().chain(&rule.allowed_headers);
+        if let Some(pattern) = patterns.into_iter().find(|p| p.matches('*').count() > 1) {
+            return Err(S3Error::InvalidRequest(format!(
+                "'{}' can not have more than one wildcard",
+                pattern
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// First rule that allows `origin` to use `method` with all of `request_headers`
+/// (lowercased)
+fn find_rule<'a>(
+    config: &'a xml::CorsConfiguration,
+    origin: &str,
+    method: &str,
+    request_headers: &[String],
+) -> Option<&'a xml::CorsRule> {
+    config.rules.iter().find(|rule| {
+        rule.allowed_origins
+            .iter()
+            .any(|pattern| wildcard_match(pattern, origin))
+            && rule.allowed_methods.iter().any(|m| m == method)
+            && request_headers.iter().all(|requested| {
+                rule.allowed_headers
+                    .iter()
+                    .any(|pattern| wildcard_match(&pattern.to_ascii_lowercase(), requested))
+            })
+    })
+}
+
+/// Match against a CORS pattern, which has at most one `*`
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            value.len() >= prefix.len() + suffix.len()
+                && value.starts_with(prefix)
+                && value.ends_with(suffix)
+        }
+        None => pattern == value,
+    }
+}
+
+/// Headers shared by preflight and actual responses
+fn set_cors_headers(headers: &mut HeaderMap, rule: &xml::CorsRule, origin: &str) {
+    if rule.allowed_origins.iter().any(|o| o == "*") {
+        insert_header(headers, header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    } else {
+        insert_header(headers, header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        insert_header(headers, header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+    }
+    insert_header(
+        headers,
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        &rule.allowed_methods.join(", "),
+    );
+    headers.append(
+        header::VARY,
+        HeaderValue::from_static(
+            "Origin, Access-Control-Request-Headers, Access-Control-Request-Method",
+        ),
+    );
+}
+
+fn insert_header(headers: &mut HeaderMap, name: header::HeaderName, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        headers.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> xml::CorsConfiguration {
+        let body = r#"<CORSConfiguration>
+            <CORSRule>
+                <AllowedOrigin>https://*.example.com</AllowedOrigin>
+                <AllowedMethod>GET</AllowedMethod>
+                <AllowedMethod>PUT</AllowedMethod>
+                <AllowedHeader>Content-*</AllowedHeader>
+                <AllowedHeader>x-amz-date</AllowedHeader>
+                <MaxAgeSeconds>3000</MaxAgeSeconds>
+            </CORSRule>
+            <CORSRule>
+                <AllowedOrigin>*</AllowedOrigin>
+                <AllowedMethod>HEAD</AllowedMethod>
+            </CORSRule>
+        </CORSConfiguration>"#;
+        xml::from_xml(body).unwrap()
+    }
+
+    #[test]
+    fn test_find_rule() {
+        let config = sample_config();
+        assert!(validate_cors(&config).is_ok());
+
+        let headers = vec!["content-type".to_string(), "x-amz-date".to_string()];
+        let rule = find_rule(&config, "https://app.example.com", "PUT", &headers).unwrap();
+        assert_eq!(rule.max_age_seconds, Some(3000));
+
+        assert!(find_rule(&config, "https://example.com", "GET", &[]).is_none());
+        assert!(find_rule(&config, "https://app.example.com", "DELETE", &[]).is_none());
+        let unlisted = vec!["authorization".to_string()];
+        assert!(find_rule(&config, "https://app.example.com", "GET", &unlisted).is_none());
+
+        // The catch-all rule only allows HEAD
+        assert!(find_rule(&config, "http://localhost:3000", "HEAD", &[]).is_some());
+        assert!(find_rule(&config, "http://localhost:3000", "GET", &[]).is_none());
+    }
+
+    #[test]
+    fn test_validate_cors() {
+        let mut config = sample_config();
+        config.rules[0].allowed_methods.push("PATCH".to_string());
+        assert!(validate_cors(&config).is_err());
+
+        let mut config = sample_config();
+        config.rules[1].allowed_origins = vec!["https://*.*.example.com".to_string()];
+        assert!(validate_cors(&config).is_err());
+    }
+}
@@ -15,15 +15,37 @@ pub enum S3Error {
     InvalidPart(String),
     InvalidPartOrder(String),
     NoSuchUpload(String),
+    NoSuchLifecycleConfiguration(String),
+    NoSuchVersion(String),
+    MethodNotAllowed(String),
     MalformedXML(String),
+    InvalidTag(String),
+    BadDigest(String),
+    InvalidDigest(String),
+    XAmzContentSHA256Mismatch(String),
     InternalError(String),
     MissingContentLength,
     SignatureDoesNotMatch(String),
     InvalidRequest(String),
+    NotImplemented(String),
+    PreconditionFailed(String),
+    NotModified,
+    NoSuchEntity(String),
+    EntityAlreadyExists(String),
+    MalformedPolicy(String),
+    NoSuchBucketPolicy(String),
+    InvalidToken(String),
+    ExpiredToken(String),
+    MalformedPOSTRequest(String),
+    InvalidPolicyDocument(String),
+    EntityTooSmall(String),
+    EntityTooLarge(String),
+    NoSuchCORSConfiguration(String),
+    AccessForbidden(String),
 }
 
 impl S3Error {
-    fn code(&self) -> &str {
+    pub(crate) fn code(&self) -> &str {
         match self {
             S3Error::AccessDenied(_) => "AccessDenied",
             S3Error::NoSuchBucket(_) => "NoSuchBucket",
@@ -36,34 +58,79 @@ impl S3Error {
             S3Error::InvalidPart(_) => "InvalidPart",
             S3Error::InvalidPartOrder(_) => "InvalidPartOrder",
             S3Error::NoSuchUpload(_) => "NoSuchUpload",
+            S3Error::NoSuchLifecycleConfiguration(_) => "NoSuchLifecycleConfiguration",
+            S3Error::NoSuchVersion(_) => "NoSuchVersion",
+            S3Error::MethodNotAllowed(_) => "MethodNotAllowed",
             S3Error::MalformedXML(_) => "MalformedXML",
+            S3Error::InvalidTag(_) => "InvalidTag",
+            S3Error::BadDigest(_) => "BadDigest",
+            S3Error::InvalidDigest(_) => "InvalidDigest",
+            S3Error::XAmzContentSHA256Mismatch(_) => "XAmzContentSHA256Mismatch",
             S3Error::InternalError(_) => "InternalError",
             S3Error::MissingContentLength => "MissingContentLength",
             S3Error::SignatureDoesNotMatch(_) => "SignatureDoesNotMatch",
             S3Error::InvalidRequest(_) => "InvalidRequest",
+            S3Error::NotImplemented(_) => "NotImplemented",
+            S3Error::PreconditionFailed(_) => "PreconditionFailed",
+            S3Error::NotModified => "NotModified",
+            S3Error::NoSuchEntity(_) => "NoSuchEntity",
+            S3Error::EntityAlreadyExists(_) => "EntityAlreadyExists",
+            S3Error::MalformedPolicy(_) => "MalformedPolicy",
+            S3Error::NoSuchBucketPolicy(_) => "NoSuchBucketPolicy",
+            S3Error::InvalidToken(_) => "InvalidToken",
+            S3Error::ExpiredToken(_) => "ExpiredToken",
+            S3Error::MalformedPOSTRequest(_) => "MalformedPOSTRequest",
+            S3Error::InvalidPolicyDocument(_) => "InvalidPolicyDocument",
+            S3Error::EntityTooSmall(_) => "EntityTooSmall",
+            S3Error::EntityTooLarge(_) => "EntityTooLarge",
+            S3Error::NoSuchCORSConfiguration(_) => "NoSuchCORSConfiguration",
+            S3Error::AccessForbidden(_) => "AccessForbidden",
         }
     }
 
     fn status_code(&self) -> StatusCode {
         match self {
-            S3Error::AccessDenied(_) | S3Error::SignatureDoesNotMatch(_) => StatusCode::FORBIDDEN,
+            S3Error::AccessDenied(_)
+            | S3Error::SignatureDoesNotMatch(_)
+            | S3Error::AccessForbidden(_) => StatusCode::FORBIDDEN,
             S3Error::NoSuchBucket(_) | S3Error::NoSuchKey(_) => StatusCode::NOT_FOUND,
             S3Error::BucketAlreadyOwnedByYou(_)
             | S3Error::BucketAlreadyExists(_)
-            | S3Error::BucketNotEmpty(_) => StatusCode::CONFLICT,
+            | S3Error::BucketNotEmpty(_)
+            | S3Error::EntityAlreadyExists(_) => StatusCode::CONFLICT,
             S3Error::InvalidArgument(_)
             | S3Error::InvalidBucketName(_)
             | S3Error::InvalidPart(_)
             | S3Error::InvalidPartOrder(_)
             | S3Error::MalformedXML(_)
-            | S3Error::InvalidRequest(_) => StatusCode::BAD_REQUEST,
-            S3Error::NoSuchUpload(_) => StatusCode::NOT_FOUND,
+            | S3Error::InvalidTag(_)
+            | S3Error::BadDigest(_)
+            | S3Error::InvalidDigest(_)
+            | S3Error::XAmzContentSHA256Mismatch(_)
+            | S3Error::InvalidRequest(_)
+            | S3Error::MalformedPolicy(_)
+            | S3Error::InvalidToken(_)
+            | S3Error::ExpiredToken(_)
+            | S3Error::MalformedPOSTRequest(_)
+            | S3Error::InvalidPolicyDocument(_)
+            | S3Error::EntityTooSmall(_)
+            | S3Error::EntityTooLarge(_) => StatusCode::BAD_REQUEST,
+            S3Error::NoSuchUpload(_)
+            | S3Error::NoSuchLifecycleConfiguration(_)
+            | S3Error::NoSuchVersion(_)
+            | S3Error::NoSuchEntity(_)
+            | S3Error::NoSuchBucketPolicy(_)
+            | S3Error::NoSuchCORSConfiguration(_) => StatusCode::NOT_FOUND,
+            S3Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
             S3Error::MissingContentLength => StatusCode::LENGTH_REQUIRED,
             S3Error::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            S3Error::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            S3Error::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            S3Error::NotModified => StatusCode::NOT_MODIFIED,
         }
     }
 
-    fn message(&self) -> &str {
+    pub(crate) fn message(&self) -> &str {
         match self {
             S3Error::AccessDenied(m) => m,
             S3Error::NoSuchBucket(m) => m,
@@ -76,11 +143,33 @@ impl S3Error {
             S3Error::InvalidPart(m) => m,
             S3Error::InvalidPartOrder(m) => m,
             S3Error::NoSuchUpload(m) => m,
+            S3Error::NoSuchLifecycleConfiguration(m) => m,
+            S3Error::NoSuchVersion(m) => m,
+            S3Error::MethodNotAllowed(m) => m,
             S3Error::MalformedXML(m) => m,
+            S3Error::InvalidTag(m) => m,
+            S3Error::BadDigest(m) => m,
+            S3Error::InvalidDigest(m) => m,
+            S3Error::XAmzContentSHA256Mismatch(m) => m,
             S3Error::InternalError(m) => m,
             S3Error::MissingContentLength => "Missing Content-Length header",
             S3Error::SignatureDoesNotMatch(m) => m,
             S3Error::InvalidRequest(m) => m,
+            S3Error::NotImplemented(m) => m,
+            S3Error::PreconditionFailed(m) => m,
+            S3Error::NotModified => "Not Modified",
+            S3Error::NoSuchEntity(m) => m,
+            S3Error::EntityAlreadyExists(m) => m,
+            S3Error::MalformedPolicy(m) => m,
+            S3Error::NoSuchBucketPolicy(m) => m,
+            S3Error::InvalidToken(m) => m,
+            S3Error::ExpiredToken(m) => m,
+            S3Error::MalformedPOSTRequest(m) => m,
+            S3Error::InvalidPolicyDocument(m) => m,
+            S3Error::EntityTooSmall(m) => m,
+            S3Error::EntityTooLarge(m) => m,
+            S3Error::NoSuchCORSConfiguration(m) => m,
+            S3Error::AccessForbidden(m) => m,
         }
     }
 
@@ -102,6 +191,10 @@ impl S3Error {
 impl IntoResponse for S3Error {
     fn into_response(self) -> Response {
         let status = self.status_code();
+        // 304 responses carry no body
+        if matches!(self, S3Error::NotModified) {
+            return status.into_response();
+        }
         let xml = self.to_xml();
         (status, [("Content-Type", "application/xml")], xml).into_response()
     }
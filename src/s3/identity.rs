@@ -0,0 +1,442 @@
+use axum::http::Method;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use percent_encoding::percent_decode_str;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::config::S3Config;
+use crate::db::entities::{access_key, bucket, session_credential, user};
+use crate::s3::acl;
+use crate::s3::auth;
+use crate::s3::error::S3Error;
+use crate::s3::policy::{self, Decision, Policy, RequestContext};
+use crate::AppState;
+
+/// Access key status that allows authentication
+pub const KEY_ACTIVE: &str = "Active";
+/// Access key status set by the admin API to block a key without deleting it
+pub const KEY_INACTIVE: &str = "Inactive";
+
+/// The authenticated caller, stored in request extensions by the auth middleware
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// Owner ID recorded on buckets: the user's UUID, or the configured
+    /// access key for the root credential
+    pub id: String,
+    pub display_name: String,
+    /// The credential from the config: owns pre-existing buckets, may use the admin API
+    pub is_root: bool,
+    /// The user's identity policy, if one is attached
+    pub policy: Option<Policy>,
+    /// Client address, for `aws:SourceIp` conditions
+    pub source_ip: Option<IpAddr>,
+    /// Authenticated with temporary credentials from STS
+    pub is_session: bool,
+    /// Session policy of those credentials, if AssumeRole was given one
+    pub session_policy: Option<Policy>,
+    /// Unsigned request: only public-read buckets and bucket policies granting "*" apply
+    pub is_anonymous: bool,
+}
+
+impl Principal {
+    /// The caller of an unsigned request
+    pub fn anonymous() -> Self {
+        Principal {
+            id: String::new(),
+            display_name: "anonymous".to_string(),
+            is_root: false,
+            policy: None,
+            source_ip: None,
+            is_session: false,
+            session_policy: None,
+            is_anonymous: true,
+        }
+    }
+
+    /// Policy evaluation context for `action` on `resource` by this principal
+    pub fn request_context(&self, action: &str, resource: String) -> RequestContext {
+        RequestContext {
+            action: action.to_string(),
+            resource,
+            user_id: self.id.clone(),
+            username: self.display_name.clone(),
+            source_ip: self.source_ip,
+            prefix: None,
+            delimiter: None,
+            is_anonymous: self.is_anonymous,
+        }
+    }
+}
+
+/// A known access key and whose it is
+pub struct Credential {
+    pub principal: Principal,
+    pub secret_access_key: String,
+    /// Token that must accompany temporary credentials (`x-amz-security-token`)
+    pub session_token: Option<String>,
+}
+
+/// Resolve an access key ID to its secret: the configured root key, an
+/// active key of an enabled user in the database, or unexpired temporary
+/// credentials issued by STS
+pub async fn lookup_credential(
+    state: &AppState,
+    access_key_id: &str,
+) -> Result<Credential, S3Error> {
+    let config = &state.config.s3;
+    if access_key_id == config.access_key_id {
+        return Ok(Credential {
+            principal: root_principal(config),
+            secret_access_key: config.secret_access_key.clone(),
+            session_token: None,
+        });
+    }
+
+    let unknown_key = || {
+        S3Error::AccessDenied(
+            "The AWS Access Key Id you provided does not exist in our records".to_string(),
+        )
+    };
+
+    let Some((key, owner)) = access_key::Entity::find_by_id(access_key_id)
+        .find_also_related(user::Entity)
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+    else {
+        return lookup_session(state, access_key_id)
+            .await?
+            .ok_or_else(unknown_key);
+    };
+    let owner = owner.ok_or_else(unknown_key)?;
+
+    if key.status != KEY_ACTIVE || owner.disabled {
+        return Err(S3Error::AccessDenied(
+            "The AWS Access Key Id you provided is disabled".to_string(),
+        ));
+    }
+
+    Ok(Credential {
+        principal: user_principal(owner)?,
+        secret_access_key: key.secret_access_key,
+        session_token: None,
+    })
+}
+
+/// Temporary credentials act as the user (or root) they were issued to,
+/// further limited by their session policy
+async fn lookup_session(
+    state: &AppState,
+    access_key_id: &str,
+) -> Result<Option<Credential>, S3Error> {
+    let Some((session, owner)) = session_credential::Entity::find_by_id(access_key_id)
+        .find_also_related(user::Entity)
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    if session.expires_at <= Utc::now() {
+        return Err(S3Error::ExpiredToken(
+            "The provided token has expired".to_string(),
+        ));
+    }
+
+    let mut principal = match (session.user_id, owner) {
+        (None, _) => root_principal(&state.config.s3),
+        (Some(_), Some(owner)) if owner.disabled => {
+            return Err(S3Error::AccessDenied(
+                "The AWS Access Key Id you provided is disabled".to_string(),
+            ));
+        }
+        (Some(_), Some(owner)) => user_principal(owner)?,
+        (Some(_), None) => return Ok(None),
+    };
+    principal.is_session = true;
+    principal.session_policy = session
+        .policy
+        .as_ref()
+        .map(Policy::from_stored)
+        .transpose()?;
+
+    Ok(Some(Credential {
+        principal,
+        secret_access_key: session.secret_access_key,
+        session_token: Some(session.session_token),
+    }))
+}
+
+fn root_principal(config: &S3Config) -> Principal {
+    Principal {
+        id: config.access_key_id.clone(),
+        display_name: config.access_key_id.clone(),
+        is_root: true,
+        policy: None,
+        source_ip: None,
+        is_session: false,
+        session_policy: None,
+        is_anonymous: false,
+    }
+}
+
+fn user_principal(user: user::Model) -> Result<Principal, S3Error> {
+    let policy = user.policy.as_ref().map(Policy::from_stored).transpose()?;
+    Ok(Principal {
+        id: user.id.to_string(),
+        display_name: user.name,
+        is_root: false,
+        policy,
+        source_ip: None,
+        is_session: false,
+        session_policy: None,
+        is_anonymous: false,
+    })
+}
+
+/// Temporary credentials are only valid together with their session token;
+/// permanent keys must not present one
+pub fn check_session_token(credential: &Credential, token: Option<&str>) -> Result<(), S3Error> {
+    match (credential.session_token.as_deref(), token) {
+        (None, None) => Ok(()),
+        (Some(expected), Some(token)) if expected == token => Ok(()),
+        _ => Err(S3Error::InvalidToken(
+            "The provided token is malformed or otherwise invalid".to_string(),
+        )),
+    }
+}
+
+/// Authorize an authenticated request. The admin API is for the root
+/// credential only (not its sessions); everything else is decided by
+/// [`check_access`] for the request's S3 action on its bucket or object.
+pub async fn authorize(
+    state: &AppState,
+    principal: &Principal,
+    method: &Method,
+    path: &str,
+    query: &str,
+) -> Result<(), S3Error> {
+    let (bucket_name, key) = request_target(path);
+    let bucket_name = bucket_name.as_str();
+    if bucket_name.starts_with('_') {
+        if principal.is_root && !principal.is_session {
+            return Ok(());
+        }
+        return Err(access_denied());
+    }
+    let params = auth::query_params(query);
+    policy::check_dispatchable(method, key.is_some(), &params)?;
+    if principal.is_root && principal.session_policy.is_none() {
+        return Ok(());
+    }
+
+    let Some(action) =
+        policy::request_action(method, !bucket_name.is_empty(), key.is_some(), &params)
+    else {
+        // Checked per resource by the handler; anonymous callers never get that far
+        if principal.is_anonymous {
+            return Err(access_denied());
+        }
+        return Ok(());
+    };
+
+    let bucket = if bucket_name.is_empty() {
+        None
+    } else {
+        bucket::Entity::find()
+            .filter(bucket::Column::Name.eq(bucket_name))
+            .one(&state.db)
+            .await
+            .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+    };
+
+    let resource = match key {
+        Some(ref key) => policy::object_arn(bucket_name, key),
+        None => policy::bucket_arn(bucket_name),
+    };
+    let mut ctx = principal.request_context(action, resource);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.clone())
+    };
+    ctx.prefix = param("prefix");
+    ctx.delimiter = param("delimiter");
+
+    check_access(principal, bucket.as_ref(), &ctx)
+}
+
+/// Bucket name and key a request path addresses, percent-decoded as the
+/// handlers' `Path` extractors see them
+fn request_target(path: &str) -> (String, Option<String>) {
+    let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+    let path = path.trim_start_matches('/');
+    match path.split_once('/') {
+        Some((bucket, key)) if !key.is_empty() => (decode(bucket), Some(decode(key))),
+        Some((bucket, _)) => (decode(bucket), None),
+        None => (decode(path), None),
+    }
+}
+
+/// Decide a request against the principal's identity policy and the bucket
+/// policy: an explicit Deny in either wins, then an Allow in either. Without
+/// one, bucket owners have full access to their buckets, and everyone may
+/// read public-read buckets. Requests not tied to an existing bucket
+/// (ListBuckets, CreateBucket, or a bucket that doesn't exist) pass unless
+/// denied or anonymous. A session policy must additionally allow the request.
+pub fn check_access(
+    principal: &Principal,
+    bucket: Option<&bucket::Model>,
+    ctx: &RequestContext,
+) -> Result<(), S3Error> {
+    if let Some(session_policy) = &principal.session_policy {
+        if session_policy.evaluate(ctx) != Decision::Allow {
+            return Err(access_denied());
+        }
+    }
+    if principal.is_root {
+        return Ok(());
+    }
+
+    let bucket_policy = bucket
+        .and_then(|b| b.policy.as_ref())
+        .map(Policy::from_stored)
+        .transpose()?;
+    let decisions = [principal.policy.as_ref(), bucket_policy.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|policy| policy.evaluate(ctx))
+        .collect::<Vec<_>>();
+
+    if decisions.contains(&Decision::Deny) {
+        return Err(access_denied());
+    }
+    if decisions.contains(&Decision::Allow) {
+        return Ok(());
+    }
+    match bucket {
+        Some(bucket) if bucket.owner_id == principal.id && !principal.is_anonymous => Ok(()),
+        Some(bucket) if acl::grants_public_access(bucket, &ctx.action) => Ok(()),
+        None if !principal.is_anonymous => Ok(()),
+        _ => Err(access_denied()),
+    }
+}
+
+/// Check read access to the source object of CopyObject or UploadPartCopy
+pub fn check_copy_source(
+    principal: &Principal,
+    source_bucket: &bucket::Model,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<(), S3Error> {
+    let action = if version_id.is_some() {
+        "s3:GetObjectVersion"
+    } else {
+        "s3:GetObject"
+    };
+    let ctx = principal.request_context(action, policy::object_arn(&source_bucket.name, key));
+    check_access(principal, Some(source_bucket), &ctx)
+}
+
+fn access_denied() -> S3Error {
+    S3Error::AccessDenied("Access Denied".to_string())
+}
+
+/// A new access key ID: "OM" followed by 18 uppercase hex characters
+pub fn generate_access_key_id() -> String {
+    let random = Uuid::new_v4().simple().to_string().to_uppercase();
+    format!("OM{}", &random[..18])
+}
+
+/// A new 40-character secret access key (30 random bytes, base64)
+pub fn generate_secret_access_key() -> String {
+    let random = [*Uuid::new_v4().as_bytes(), *Uuid::new_v4().as_bytes()].concat();
+    BASE64_STANDARD.encode(&random[..30])
+}
+
+/// A new session token for temporary credentials (48 random bytes, base64)
+pub fn generate_session_token() -> String {
+    let random = [
+        *Uuid::new_v4().as_bytes(),
+        *Uuid::new_v4().as_bytes(),
+        *Uuid::new_v4().as_bytes(),
+    ]
+    .concat();
+    BASE64_STANDARD.encode(random)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys() {
+        let access_key_id = generate_access_key_id();
+        assert_eq!(access_key_id.len(), 20);
+        assert!(access_key_id.starts_with("OM"));
+        assert!(access_key_id
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+
+        let secret = generate_secret_access_key();
+        assert_eq!(secret.len(), 40);
+        assert_ne!(secret, generate_secret_access_key());
+    }
+
+    #[test]
+    fn test_encoded_bucket_name_is_authorized_as_decoded() {
+        let (bucket_name, key) = request_target("/my%62ucket/secret%2Fa.txt");
+        assert_eq!(bucket_name, "mybucket");
+        assert_eq!(key.as_deref(), Some("secret/a.txt"));
+        assert_eq!(request_target("/mybucket/"), ("mybucket".to_string(), None));
+
+        // The decoded name finds the bucket, so its owner check applies
+        let bucket = bucket::Model {
+            id: Uuid::new_v4(),
+            name: bucket_name,
+            owner_id: "owner".to_string(),
+            region: "us-east-1".to_string(),
+            created_at: Utc::now(),
+            lifecycle: None,
+            versioning: None,
+            policy: None,
+            acl: "private".to_string(),
+            cors: None,
+        };
+        let other_user = Principal {
+            id: "other".to_string(),
+            display_name: "other".to_string(),
+            is_anonymous: false,
+            ..Principal::anonymous()
+        };
+        let ctx = other_user.request_context(
+            "s3:GetObject",
+            policy::object_arn(&bucket.name, key.as_deref().unwrap()),
+        );
+        assert!(matches!(
+            check_access(&other_user, Some(&bucket), &ctx),
+            Err(S3Error::AccessDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_session_token() {
+        let credential = |session_token: Option<&str>| Credential {
+            principal: Principal::anonymous(),
+            secret_access_key: String::new(),
+            session_token: session_token.map(str::to_string),
+        };
+
+        assert!(check_session_token(&credential(None), None).is_ok());
+        assert!(check_session_token(&credential(Some("token")), Some("token")).is_ok());
+        // Temporary credentials need their token; permanent keys take none
+        assert!(check_session_token(&credential(Some("token")), None).is_err());
+        assert!(check_session_token(&credential(Some("token")), Some("other")).is_err());
+        assert!(check_session_token(&credential(None), Some("token")).is_err());
+    }
+}
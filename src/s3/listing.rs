@@ -0,0 +1,498 @@
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    Select,
+};
+use uuid::Uuid;
+
+use crate::db::entities::object;
+
+/// Rows fetched per query while rolling keys up into common prefixes.
+/// Kept small because every common prefix restarts the scan past itself.
+const DELIMITER_BATCH_SIZE: u64 = 100;
+
+/// One page of a bucket listing
+pub struct ListPage {
+    pub objects: Vec<object::Model>,
+    pub common_prefixes: Vec<String>,
+    pub is_truncated: bool,
+    /// Last key or common prefix returned; resume point for the next page
+    pub next_marker: Option<String>,
+}
+
+/// One page of a version listing (ListObjectVersions)
+pub struct VersionPage {
+    /// Object versions and delete markers, by key and then newest first
+    pub versions: Vec<object::Model>,
+    pub common_prefixes: Vec<String>,
+    pub is_truncated: bool,
+    pub next_key_marker: Option<String>,
+    pub next_version_id_marker: Option<String>,
+}
+
+/// Lower bound of the next range to scan
+#[derive(Debug, PartialEq)]
+enum LowerBound {
+    Inclusive(String),
+    Exclusive(String),
+    /// Versions of the same key older than this one, then later keys
+    AfterVersion(Box<object::Model>),
+}
+
+/// Rows and common prefixes of one page, in listing order
+struct Scan {
+    rows: Vec<object::Model>,
+    common_prefixes: Vec<String>,
+    is_truncated: bool,
+    /// Whether the last entry of the page is a common prefix (rather than a row)
+    ends_with_prefix: bool,
+}
+
+/// List up to `max_keys` objects and common prefixes of a bucket, in key order,
+/// starting after `marker`. Ordering, bounds and limits are all pushed into the
+/// query, and each common prefix is skipped with a single seek, so the cost is
+/// proportional to the page size rather than to the bucket size.
+pub async fn list_objects(
+    db: &DatabaseConnection,
+    bucket_id: Uuid,
+    prefix: &str,
+    delimiter: Option<&str>,
+    marker: Option<&str>,
+    max_keys: usize,
+) -> Result<ListPage> {
+    let delimiter = delimiter.filter(|d| !d.is_empty());
+    let Some(lower) = start_bound(prefix, delimiter, marker) else {
+        return Ok(ListPage {
+            objects: Vec::new(),
+            common_prefixes: Vec::new(),
+            is_truncated: false,
+            next_marker: None,
+        });
+    };
+
+    let scan = scan(db, bucket_id, prefix, delimiter, lower, max_keys, false).await?;
+
+    let next_marker = next_marker(&scan);
+    Ok(ListPage {
+        objects: scan.rows,
+        common_prefixes: scan.common_prefixes,
+        is_truncated: scan.is_truncated,
+        next_marker,
+    })
+}
+
+/// List up to `max_keys` object versions, delete markers and common prefixes,
+/// resuming after `version_marker` if given, otherwise after `key_marker`
+pub async fn list_object_versions(
+    db: &DatabaseConnection,
+    bucket_id: Uuid,
+    prefix: &str,
+    delimiter: Option<&str>,
+    key_marker: Option<&str>,
+    version_marker: Option<object::Model>,
+    max_keys: usize,
+) -> Result<VersionPage> {
+    let delimiter = delimiter.filter(|d| !d.is_empty());
+    let Some(lower) = version_start_bound(prefix, delimiter, key_marker, version_marker) else {
+        return Ok(VersionPage {
+            versions: Vec::new(),
+            common_prefixes: Vec::new(),
+            is_truncated: false,
+            next_key_marker: None,
+            next_version_id_marker: None,
+        });
+    };
+
+    let scan = scan(db, bucket_id, prefix, delimiter, lower, max_keys, true).await?;
+
+    let (next_key_marker, next_version_id_marker) = next_version_markers(&scan);
+    Ok(VersionPage {
+        versions: scan.rows,
+        common_prefixes: scan.common_prefixes,
+        is_truncated: scan.is_truncated,
+        next_key_marker,
+        next_version_id_marker,
+    })
+}
+
+/// Where a listing resumes: after `marker` (skipping the whole common prefix
+/// it belongs to, if any), or at the start of `prefix`.
+/// `None` if nothing can follow the marker.
+fn start_bound(prefix: &str, delimiter: Option<&str>, marker: Option<&str>) -> Option<LowerBound> {
+    match marker {
+        Some(marker) if marker >= prefix => match common_prefix_of(marker, prefix, delimiter) {
+            // Resuming after a common prefix: skip every key under it
+            Some(cp) => successor(cp).map(LowerBound::Inclusive),
+            None => Some(LowerBound::Exclusive(marker.to_string())),
+        },
+        _ => Some(LowerBound::Inclusive(prefix.to_string())),
+    }
+}
+
+/// Where a version listing resumes: after the `version_marker` version if
+/// given (older versions of its key, then later keys), otherwise as `start_bound`
+fn version_start_bound(
+    prefix: &str,
+    delimiter: Option<&str>,
+    key_marker: Option<&str>,
+    version_marker: Option<object::Model>,
+) -> Option<LowerBound> {
+    match version_marker {
+        Some(version) => Some(LowerBound::AfterVersion(Box::new(version))),
+        None => start_bound(prefix, delimiter, key_marker),
+    }
+}
+
+/// Resume point after a page of a listing: its last key or common prefix,
+/// if the listing was truncated
+fn next_marker(scan: &Scan) -> Option<String> {
+    match (scan.is_truncated, scan.ends_with_prefix) {
+        (false, _) => None,
+        (true, true) => scan.common_prefixes.last().cloned(),
+        (true, false) => scan.rows.last().map(|o| o.key.clone()),
+    }
+}
+
+/// `NextKeyMarker` and `NextVersionIdMarker` after a page of a version listing.
+/// A page ending in a common prefix resumes after the prefix, with no version id.
+fn next_version_markers(scan: &Scan) -> (Option<String>, Option<String>) {
+    match (scan.is_truncated, scan.ends_with_prefix) {
+        (false, _) => (None, None),
+        (true, true) => (scan.common_prefixes.last().cloned(), None),
+        (true, false) => match scan.rows.last() {
+            Some(o) => (Some(o.key.clone()), Some(o.version_id.clone())),
+            None => (None, None),
+        },
+    }
+}
+
+/// Collect up to `max_keys` rows and common prefixes under `prefix`, from `lower` on.
+/// With `all_versions`, every version and delete marker is a row; otherwise
+/// only current versions that are not delete markers.
+async fn scan(
+    db: &DatabaseConnection,
+    bucket_id: Uuid,
+    prefix: &str,
+    delimiter: Option<&str>,
+    mut lower: LowerBound,
+    max_keys: usize,
+    all_versions: bool,
+) -> Result<Scan> {
+    let upper = successor(prefix);
+    let mut scan = Scan {
+        rows: Vec::new(),
+        common_prefixes: Vec::new(),
+        is_truncated: false,
+        ends_with_prefix: false,
+    };
+    let mut count = 0;
+
+    'scan: loop {
+        if count == max_keys {
+            // Page is full; probe for anything beyond it
+            let more = range_query(bucket_id, &lower, upper.as_deref(), all_versions)
+                .limit(1)
+                .all(db)
+                .await
+                .context("Failed to query objects")?;
+            scan.is_truncated = !more.is_empty();
+            break;
+        }
+
+        let remaining = (max_keys - count) as u64;
+        let batch_size = match delimiter {
+            Some(_) => remaining.min(DELIMITER_BATCH_SIZE),
+            None => remaining,
+        };
+
+        let mut query = range_query(bucket_id, &lower, upper.as_deref(), all_versions)
+            .order_by_asc(object::Column::Key);
+        if all_versions {
+            query = query
+                .order_by_desc(object::Column::CreatedAt)
+                .order_by_desc(object::Column::Id);
+        }
+        let batch = query
+            .limit(batch_size)
+            .all(db)
+            .await
+            .context("Failed to query objects")?;
+        let exhausted = (batch.len() as u64) < batch_size;
+
+        for obj in batch {
+            if let Some(cp) = common_prefix_of(&obj.key, prefix, delimiter) {
+                let cp = cp.to_string();
+                scan.common_prefixes.push(cp.clone());
+                scan.ends_with_prefix = true;
+                count += 1;
+
+                match successor(&cp) {
+                    Some(next) => lower = LowerBound::Inclusive(next),
+                    None => break 'scan,
+                }
+                continue 'scan;
+            }
+
+            lower = if all_versions {
+                LowerBound::AfterVersion(Box::new(obj.clone()))
+            } else {
+                LowerBound::Exclusive(obj.key.clone())
+            };
+            scan.rows.push(obj);
+            scan.ends_with_prefix = false;
+            count += 1;
+        }
+
+        if exhausted {
+            break;
+        }
+    }
+
+    Ok(scan)
+}
+
+/// Rows of a bucket from `lower` up to (excluding) keys >= `upper`
+fn range_query(
+    bucket_id: Uuid,
+    lower: &LowerBound,
+    upper: Option<&str>,
+    all_versions: bool,
+) -> Select<object::Entity> {
+    let mut query = object::Entity::find()
+        .filter(object::Column::BucketId.eq(bucket_id))
+        .filter(object::Column::DeletedAt.is_null());
+    if !all_versions {
+        query = query
+            .filter(object::Column::IsLatest.eq(true))
+            .filter(object::Column::IsDeleteMarker.eq(false));
+    }
+
+    query = match lower {
+        LowerBound::Inclusive(key) => query.filter(object::Column::Key.gte(key.as_str())),
+        LowerBound::Exclusive(key) => query.filter(object::Column::Key.gt(key.as_str())),
+        LowerBound::AfterVersion(version) => query.filter(
+            Condition::any()
+                .add(object::Column::Key.gt(version.key.as_str()))
+                .add(
+                    Condition::all()
+                        .add(object::Column::Key.eq(version.key.as_str()))
+                        .add(
+                            Condition::any()
+                                .add(object::Column::CreatedAt.lt(version.created_at))
+                                .add(
+                                    Condition::all()
+                                        .add(object::Column::CreatedAt.eq(version.created_at))
+                                        .add(object::Column::Id.lt(version.id)),
+                                ),
+                        ),
+                ),
+        ),
+    };
+    if let Some(upper) = upper {
+        query = query.filter(object::Column::Key.lt(upper));
+    }
+    query
+}
+
+/// The common prefix `key` rolls up into: everything up to and including the
+/// first delimiter after `prefix`, or `None` if the key is listed on its own
+pub fn common_prefix_of<'a>(
+    key: &'a str,
+    prefix: &str,
+    delimiter: Option<&str>,
+) -> Option<&'a str> {
+    let delimiter = delimiter?;
+    let rest = key.strip_prefix(prefix)?;
+    let pos = rest.find(delimiter)?;
+    Some(&key[..prefix.len() + pos + delimiter.len()])
+}
+
+/// Smallest string greater than every string starting with `s`
+/// (`None` if `s` is empty, i.e. there is no upper bound)
+pub fn successor(s: &str) -> Option<String> {
+    let mut chars: Vec<char> = s.chars().collect();
+    while let Some(last) = chars.pop() {
+        let next = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32);
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+/// Encode a resume point as an opaque continuation token
+pub fn encode_continuation_token(marker: &str) -> String {
+    URL_SAFE_NO_PAD.encode(marker.as_bytes())
+}
+
+/// Decode a continuation token produced by `encode_continuation_token`
+pub fn decode_continuation_token(token: &str) -> Option<String> {
+    let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Characters left as-is by `encoding-type=url`: RFC 3986 unreserved plus `/`
+const KEY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'/');
+
+/// URL-encode a key or prefix for a listing requested with `encoding-type=url`
+pub fn encode_key(key: String, url_encode: bool) -> String {
+    if url_encode {
+        utf8_percent_encode(&key, KEY_ENCODE_SET).to_string()
+    } else {
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(key: &str, version_id: &str) -> object::Model {
+        let now = chrono::Utc::now();
+        object::Model {
+            id: Uuid::new_v4(),
+            bucket_id: Uuid::nil(),
+            key: key.to_string(),
+            size: 0,
+            etag: String::new(),
+            content_type: String::new(),
+            metadata: None,
+            chunk_count: 0,
+            created_at: now,
+            updated_at: now,
+            version_id: version_id.to_string(),
+            is_latest: false,
+            is_delete_marker: false,
+            deleted_at: None,
+            tags: None,
+            checksum_algorithm: None,
+            checksum: None,
+            headers: None,
+            sse_customer_key_md5: None,
+        }
+    }
+
+    fn page(rows: Vec<object::Model>, prefixes: &[&str], truncated: bool) -> Scan {
+        let ends_with_prefix = !prefixes.is_empty() && rows.is_empty();
+        Scan {
+            rows,
+            common_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            is_truncated: truncated,
+            ends_with_prefix,
+        }
+    }
+
+    #[test]
+    fn test_start_bound() {
+        assert_eq!(
+            start_bound("logs/", Some("/"), None),
+            Some(LowerBound::Inclusive("logs/".to_string()))
+        );
+        // A marker before the prefix starts at the prefix
+        assert_eq!(
+            start_bound("logs/", None, Some("a")),
+            Some(LowerBound::Inclusive("logs/".to_string()))
+        );
+        assert_eq!(
+            start_bound("", None, Some("logs/a")),
+            Some(LowerBound::Exclusive("logs/a".to_string()))
+        );
+        // Resuming inside a common prefix skips the rest of it
+        assert_eq!(
+            start_bound("", Some("/"), Some("logs/")),
+            Some(LowerBound::Inclusive("logs0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_version_start_bound() {
+        let marker = version("logs/a", "v2");
+        assert_eq!(
+            version_start_bound("", Some("/"), Some("logs/a"), Some(marker.clone())),
+            Some(LowerBound::AfterVersion(Box::new(marker)))
+        );
+        assert_eq!(
+            version_start_bound("", None, Some("logs/a"), None),
+            Some(LowerBound::Exclusive("logs/a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_next_markers() {
+        let rows = vec![version("a", "v1"), version("b", "v3")];
+        let scan = page(rows, &[], true);
+        assert_eq!(next_marker(&scan).as_deref(), Some("b"));
+        assert_eq!(
+            next_version_markers(&scan),
+            (Some("b".to_string()), Some("v3".to_string()))
+        );
+
+        // Not truncated: nothing to resume from
+        let scan = page(vec![version("a", "v1")], &[], false);
+        assert_eq!(next_marker(&scan), None);
+        assert_eq!(next_version_markers(&scan), (None, None));
+
+        // Ending in a common prefix resumes after it, without a version id
+        let scan = page(Vec::new(), &["logs/"], true);
+        assert_eq!(next_marker(&scan).as_deref(), Some("logs/"));
+        assert_eq!(
+            next_version_markers(&scan),
+            (Some("logs/".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn test_successor() {
+        assert_eq!(successor("photos/").as_deref(), Some("photos0"));
+        assert_eq!(successor("a").as_deref(), Some("b"));
+        assert_eq!(successor(""), None);
+        assert_eq!(successor("a\u{10FFFF}").as_deref(), Some("b"));
+        // Skips the surrogate range
+        assert_eq!(successor("\u{D7FF}").as_deref(), Some("\u{E000}"));
+    }
+
+    #[test]
+    fn test_common_prefix_of() {
+        assert_eq!(
+            common_prefix_of("photos/2024/a.jpg", "", Some("/")),
+            Some("photos/")
+        );
+        assert_eq!(
+            common_prefix_of("photos/2024/a.jpg", "photos/", Some("/")),
+            Some("photos/2024/")
+        );
+        assert_eq!(common_prefix_of("photos/a.jpg", "photos/", Some("/")), None);
+        assert_eq!(common_prefix_of("photos/a.jpg", "", None), None);
+        assert_eq!(common_prefix_of("a--b--c", "", Some("--")), Some("a--"));
+    }
+
+    #[test]
+    fn test_continuation_token_roundtrip() {
+        let token = encode_continuation_token("logs/2024-01-01/app.log");
+        assert!(!token.contains('/'));
+        assert_eq!(
+            decode_continuation_token(&token).as_deref(),
+            Some("logs/2024-01-01/app.log")
+        );
+        assert_eq!(decode_continuation_token("not base64!"), None);
+    }
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(
+            encode_key("dir/a b+c\u{1}.txt".to_string(), true),
+            "dir/a%20b%2Bc%01.txt"
+        );
+        assert_eq!(encode_key("a b".to_string(), false), "a b");
+        assert_eq!(encode_key("é".to_string(), true), "%C3%A9");
+    }
+}
@@ -1,8 +1,20 @@
+pub mod acl;
+pub mod admin;
 pub mod auth;
 pub mod bucket;
+pub mod chunked;
+pub mod conditional;
+pub mod cors;
 pub mod error;
+pub mod identity;
+pub mod listing;
 pub mod multipart;
 pub mod object;
+pub mod policy;
+pub mod post_object;
 pub mod router;
+pub mod sigv2;
+pub mod sse;
 pub mod sts;
+pub mod tagging;
 pub mod xml;
@@ -1,20 +1,27 @@
 use axum::{
     body::Body,
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
 use chrono::Utc;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set,
 };
 use serde::Deserialize;
+use std::ops::Range;
 use uuid::Uuid;
 
 use crate::db::entities::{bucket, multipart_part, multipart_upload, object};
+use crate::s3::conditional;
 use crate::s3::error::S3Error;
+use crate::s3::identity::{self, Principal};
+use crate::s3::object as object_handlers;
+use crate::s3::sse;
+use crate::s3::tagging;
 use crate::s3::xml;
-use crate::storage::hasher;
+use crate::storage::hasher::{self, Checksum, ChecksumAlgorithm};
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -66,6 +73,10 @@ pub async fn create_multipart_upload(
         Some(serde_json::Value::Object(user_metadata))
     };
 
+    let tags = tagging::tags_from_header(&headers)?;
+    let checksum_algorithm = object_handlers::requested_checksum_algorithm(&headers)?;
+    let key_md5 = sse::customer_key(&headers)?.map(|k| k.key_md5);
+
     let upload_id = Uuid::new_v4();
 
     let upload = multipart_upload::ActiveModel {
@@ -75,6 +86,10 @@ pub async fn create_multipart_upload(
         content_type: Set(Some(content_type)),
         metadata: Set(metadata_json),
         created_at: Set(Utc::now()),
+        tags: Set(tags),
+        checksum_algorithm: Set(checksum_algorithm.map(|a| a.as_str().to_string())),
+        headers: Set(object_handlers::stored_headers(&headers)),
+        sse_customer_key_md5: Set(key_md5.clone()),
     };
 
     upload
@@ -90,19 +105,28 @@ pub async fn create_multipart_upload(
 
     let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    Ok((
+    let mut response = (
         StatusCode::OK,
         [("Content-Type", "application/xml")],
         xml_body,
     )
-        .into_response())
+        .into_response();
+    if let Some(algorithm) = checksum_algorithm {
+        response.headers_mut().insert(
+            "x-amz-checksum-algorithm",
+            HeaderValue::from_static(algorithm.as_str()),
+        );
+    }
+    sse::set_response_headers(&mut response, key_md5.as_deref());
+    Ok(response)
 }
 
 /// PUT /{bucket}/{key}?partNumber={n}&uploadId={id} — Upload part
 pub async fn upload_part(
     State(state): State<AppState>,
-    Path((_bucket_name, _key)): Path<(String, String)>,
+    Path((bucket_name, key)): Path<(String, String)>,
     Query(params): Query<MultipartQuery>,
+    headers: HeaderMap,
     body: axum::body::Bytes,
 ) -> Result<Response, S3Error> {
     let upload_id = params
@@ -114,18 +138,58 @@ pub async fn upload_part(
         .part_number
         .ok_or_else(|| S3Error::InvalidArgument("Missing partNumber".to_string()))?;
 
-    let upload_uuid = Uuid::parse_str(upload_id)
-        .map_err(|_| S3Error::NoSuchUpload("Invalid upload ID".to_string()))?;
-
     // Verify upload exists
-    let _upload = multipart_upload::Entity::find_by_id(upload_uuid)
-        .one(&state.db)
-        .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
-        .ok_or_else(|| S3Error::NoSuchUpload(format!("Upload '{}' not found", upload_id)))?;
+    let upload = find_upload(&state, upload_id, &bucket_name, &key).await?;
+    let upload_uuid = upload.id;
+
+    // Parts carry the upload's checksum algorithm (and may not switch to another)
+    let upload_algorithm = upload
+        .checksum_algorithm
+        .as_deref()
+        .and_then(ChecksumAlgorithm::parse);
+    let checksum = object_handlers::request_checksum(&headers, &body, upload_algorithm)?;
+    if let (Some(expected), Some(checksum)) = (upload_algorithm, &checksum) {
+        if checksum.algorithm != expected {
+            return Err(S3Error::InvalidRequest(format!(
+                "Checksum Type mismatch occurred, expected checksum Type: {}, actual checksum Type: {}",
+                expected.as_str().to_lowercase(),
+                checksum.algorithm.as_str().to_lowercase()
+            )));
+        }
+    }
+
+    let encryption = sse::customer_key(&headers)?;
+    sse::check_upload_key(upload.sse_customer_key_md5.as_deref(), encryption.as_ref())?;
+
+    // Compute ETag (MD5 of part data; keyed for encrypted parts so it reveals nothing)
+    let etag = match &encryption {
+        Some(encryption) => encryption.etag(&body),
+        None => format!("\"{}\"", hasher::compute_md5(&body)),
+    };
+
+    let pipeline = state.pipeline.lock().await;
+    if let Some(encryption) = &encryption {
+        // Encrypted parts are stored as drafts right away rather than in a temp file
+        pipeline
+            .stage_part(
+                &upload,
+                part_number,
+                &body,
+                etag.clone(),
+                checksum.as_ref().map(|c| c.value.clone()),
+                encryption,
+            )
+            .await
+            .map_err(|e| S3Error::InternalError(e.to_string()))?;
+        drop(pipeline);
+        return Ok(part_response(&etag, checksum, Some(&encryption.key_md5)));
+    }
 
-    // Compute ETag (MD5 of part data)
-    let etag = format!("\"{}\"", hasher::compute_md5(&body));
+    // Remove any existing part with this number (overwrite semantics for same part number)
+    pipeline
+        .remove_part(upload_uuid, part_number)
+        .await
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
     // Save part data to temp file
     let temp_dir = &state.config.storage.temp_dir;
@@ -133,20 +197,11 @@ pub async fn upload_part(
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    let temp_path = temp_dir.join(format!("{}-{}", upload_id, part_number));
+    let temp_path = temp_dir.join(format!("{}-{}", upload_uuid, part_number));
     tokio::fs::write(&temp_path, &body)
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    // Upsert part record
-    // Delete existing if exists (overwrite semantics for same part number)
-    multipart_part::Entity::delete_many()
-        .filter(multipart_part::Column::UploadId.eq(upload_uuid))
-        .filter(multipart_part::Column::PartNumber.eq(part_number))
-        .exec(&state.db)
-        .await
-        .ok();
-
     let part = multipart_part::ActiveModel {
         id: Set(Uuid::new_v4()),
         upload_id: Set(upload_uuid),
@@ -155,6 +210,7 @@ pub async fn upload_part(
         etag: Set(etag.clone()),
         temp_path: Set(Some(temp_path.to_string_lossy().to_string())),
         created_at: Set(Utc::now()),
+        checksum: Set(checksum.as_ref().map(|c| c.value.clone())),
     };
 
     part.insert(&state.db)
@@ -168,29 +224,196 @@ pub async fn upload_part(
         body.len()
     );
 
-    Ok((StatusCode::OK, [("ETag", etag.as_str())]).into_response())
+    Ok(part_response(&etag, checksum, None))
 }
 
-/// POST /{bucket}/{key}?uploadId={id} — Complete multipart upload
-pub async fn complete_multipart_upload(
+/// Response to UploadPart: the part ETag, checksum and encryption headers
+fn part_response(etag: &str, checksum: Option<Checksum>, key_md5: Option<&str>) -> Response {
+    let mut response = (StatusCode::OK, [("ETag", etag)]).into_response();
+    if let Some(checksum) = checksum {
+        if let Ok(value) = HeaderValue::from_str(&checksum.value) {
+            response
+                .headers_mut()
+                .insert(checksum.algorithm.header_name(), value);
+        }
+    }
+    sse::set_response_headers(&mut response, key_md5);
+    response
+}
+
+/// PUT /{bucket}/{key}?partNumber={n}&uploadId={id} with x-amz-copy-source — Upload part (copy)
+pub async fn upload_part_copy(
     State(state): State<AppState>,
     Path((bucket_name, key)): Path<(String, String)>,
     Query(params): Query<MultipartQuery>,
-    body: axum::body::Bytes,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
 ) -> Result<Response, S3Error> {
     let upload_id = params
         .upload_id
         .as_ref()
         .ok_or_else(|| S3Error::InvalidArgument("Missing uploadId".to_string()))?;
 
+    let part_number = params
+        .part_number
+        .ok_or_else(|| S3Error::InvalidArgument("Missing partNumber".to_string()))?;
+
+    let upload = find_upload(&state, upload_id, &bucket_name, &key).await?;
+
+    let copy_source = headers
+        .get("x-amz-copy-source")
+        .ok_or_else(|| S3Error::InvalidArgument("Missing x-amz-copy-source".to_string()))?;
+    let (source_bucket_name, source_key, source_version_id) =
+        object_handlers::parse_copy_source(copy_source)?;
+
+    // Find source bucket and object
+    let source_bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&source_bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| {
+            S3Error::NoSuchBucket(format!("Source bucket '{}' not found", source_bucket_name))
+        })?;
+
+    identity::check_copy_source(
+        &principal,
+        &source_bucket,
+        &source_key,
+        source_version_id.as_deref(),
+    )?;
+
+    let source_object = object_handlers::find_object(
+        &state.db,
+        source_bucket.id,
+        &source_key,
+        source_version_id.as_deref(),
+    )
+    .await?;
+    let source_encryption = sse::copy_source_key(&headers)?;
+    sse::check_key(
+        source_object.sse_customer_key_md5.as_deref(),
+        source_encryption.as_ref(),
+    )?;
+    let encryption = sse::customer_key(&headers)?;
+    sse::check_upload_key(upload.sse_customer_key_md5.as_deref(), encryption.as_ref())?;
+
+    let source_size = source_object.size as u64;
+    let range = match headers
+        .get("x-amz-copy-source-range")
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(range_header) => parse_copy_source_range(range_header, source_size)?,
+        None => 0..source_size,
+    };
+
+    // Stage the part via storage pipeline (references existing chunks where possible)
+    let pipeline = state.pipeline.lock().await;
+    let part = pipeline
+        .stage_part_copy(
+            &upload,
+            part_number,
+            &source_object,
+            range,
+            source_encryption.as_ref(),
+            encryption.as_ref(),
+        )
+        .await
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    tracing::info!(
+        "Part {} of upload {} copied from {}/{} ({} bytes)",
+        part_number,
+        upload_id,
+        source_bucket_name,
+        source_key,
+        part.size
+    );
+
+    let result = xml::CopyPartResult {
+        last_modified: part.created_at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        etag: part.etag.clone(),
+    };
+
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let mut response = (
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response();
+    sse::set_response_headers(&mut response, upload.sse_customer_key_md5.as_deref());
+    Ok(response)
+}
+
+/// Look up the upload named by `uploadId`. It must belong to the bucket and key
+/// in the path, since that is what the request was authorized against.
+async fn find_upload(
+    state: &AppState,
+    upload_id: &str,
+    bucket_name: &str,
+    key: &str,
+) -> Result<multipart_upload::Model, S3Error> {
+    let not_found = || S3Error::NoSuchUpload(format!("Upload '{}' not found", upload_id));
+
     let upload_uuid = Uuid::parse_str(upload_id)
         .map_err(|_| S3Error::NoSuchUpload("Invalid upload ID".to_string()))?;
-
     let upload = multipart_upload::Entity::find_by_id(upload_uuid)
         .one(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
-        .ok_or_else(|| S3Error::NoSuchUpload(format!("Upload '{}' not found", upload_id)))?;
+        .ok_or_else(not_found)?;
+
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    if upload.key != key || bucket.map(|b| b.id) != Some(upload.bucket_id) {
+        return Err(not_found());
+    }
+    Ok(upload)
+}
+
+/// Parse `x-amz-copy-source-range: bytes=first-last` into a half-open byte range
+fn parse_copy_source_range(header: &str, source_size: u64) -> Result<Range<u64>, S3Error> {
+    let invalid = || {
+        S3Error::InvalidArgument(format!(
+            "Range specified is not valid for source object of size: {}",
+            source_size
+        ))
+    };
+
+    let (first, last) = header
+        .strip_prefix("bytes=")
+        .and_then(|r| r.split_once('-'))
+        .ok_or_else(invalid)?;
+    let first: u64 = first.trim().parse().map_err(|_| invalid())?;
+    let last: u64 = last.trim().parse().map_err(|_| invalid())?;
+
+    if first > last || last >= source_size {
+        return Err(invalid());
+    }
+
+    Ok(first..last + 1)
+}
+
+/// POST /{bucket}/{key}?uploadId={id} — Complete multipart upload
+pub async fn complete_multipart_upload(
+    State(state): State<AppState>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(params): Query<MultipartQuery>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let upload_id = params
+        .upload_id
+        .as_ref()
+        .ok_or_else(|| S3Error::InvalidArgument("Missing uploadId".to_string()))?;
+
+    let upload = find_upload(&state, upload_id, &bucket_name, &key).await?;
+    let upload_uuid = upload.id;
 
     // Parse the CompleteMultipartUpload XML request
     let body_str = std::str::from_utf8(&body)
@@ -221,8 +444,8 @@ pub async fn complete_multipart_upload(
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
 
-    // Concatenate all part data
-    let mut combined_data = Vec::new();
+    // Collect the requested parts in order
+    let mut parts = Vec::with_capacity(complete_request.parts.len());
     for requested_part in &complete_request.parts {
         let stored = stored_parts
             .iter()
@@ -230,62 +453,27 @@ pub async fn complete_multipart_upload(
             .ok_or_else(|| {
                 S3Error::InvalidPart(format!("Part {} not found", requested_part.part_number))
             })?;
-
-        let temp_path = stored.temp_path.as_ref().ok_or_else(|| {
-            S3Error::InternalError(format!("No temp path for part {}", stored.part_number))
-        })?;
-
-        let part_data = tokio::fs::read(temp_path)
-            .await
-            .map_err(|e| S3Error::InternalError(format!("Failed to read part data: {}", e)))?;
-
-        combined_data.extend_from_slice(&part_data);
+        parts.push(stored.clone());
     }
 
     // Get the bucket
-    let _bucket = bucket::Entity::find_by_id(upload.bucket_id)
+    let bucket = bucket::Entity::find_by_id(upload.bucket_id)
         .one(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket("Bucket not found".to_string()))?;
 
-    // Upload the combined data via storage pipeline
-    let content_type = upload
-        .content_type
-        .unwrap_or_else(|| "application/octet-stream".to_string());
-
+    // Assemble the object via storage pipeline (also cleans up parts and the upload);
+    // preconditions are checked under the lock
     let pipeline = state.pipeline.lock().await;
+    conditional::check_write_preconditions(&state.db, &headers, bucket.id, &upload.key).await?;
     let obj = pipeline
-        .upload(
-            upload.bucket_id,
-            &upload.key,
-            &combined_data,
-            &content_type,
-            upload.metadata,
-        )
+        .complete_multipart_upload(&upload, &parts)
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
     drop(pipeline);
 
-    // Cleanup: delete temp files and DB records
-    for stored in &stored_parts {
-        if let Some(ref temp_path) = stored.temp_path {
-            tokio::fs::remove_file(temp_path).await.ok();
-        }
-    }
-
-    multipart_part::Entity::delete_many()
-        .filter(multipart_part::Column::UploadId.eq(upload_uuid))
-        .exec(&state.db)
-        .await
-        .ok();
-
-    multipart_upload::Entity::delete_by_id(upload_uuid)
-        .exec(&state.db)
-        .await
-        .ok();
-
     let result = xml::CompleteMultipartUploadResult {
         location: format!("/{}/{}", bucket_name, key),
         bucket: bucket_name,
@@ -295,18 +483,21 @@ pub async fn complete_multipart_upload(
 
     let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    Ok((
+    let mut response = (
         StatusCode::OK,
         [("Content-Type", "application/xml")],
         xml_body,
     )
-        .into_response())
+        .into_response();
+    object_handlers::set_version_header(&mut response, &bucket, &obj.version_id);
+    sse::set_response_headers(&mut response, obj.sse_customer_key_md5.as_deref());
+    Ok(response)
 }
 
 /// DELETE /{bucket}/{key}?uploadId={id} — Abort multipart upload
 pub async fn abort_multipart_upload(
     State(state): State<AppState>,
-    Path((_bucket_name, _key)): Path<(String, String)>,
+    Path((bucket_name, key)): Path<(String, String)>,
     Query(params): Query<MultipartQuery>,
 ) -> Result<Response, S3Error> {
     let upload_id = params
@@ -314,33 +505,13 @@ pub async fn abort_multipart_upload(
         .as_ref()
         .ok_or_else(|| S3Error::InvalidArgument("Missing uploadId".to_string()))?;
 
-    let upload_uuid = Uuid::parse_str(upload_id)
-        .map_err(|_| S3Error::NoSuchUpload("Invalid upload ID".to_string()))?;
-
-    // Delete temp files
-    let parts = multipart_part::Entity::find()
-        .filter(multipart_part::Column::UploadId.eq(upload_uuid))
-        .all(&state.db)
-        .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
-
-    for part in &parts {
-        if let Some(ref temp_path) = part.temp_path {
-            tokio::fs::remove_file(temp_path).await.ok();
-        }
-    }
-
-    // Cleanup DB
-    multipart_part::Entity::delete_many()
-        .filter(multipart_part::Column::UploadId.eq(upload_uuid))
-        .exec(&state.db)
-        .await
-        .ok();
+    let upload = find_upload(&state, upload_id, &bucket_name, &key).await?;
 
-    multipart_upload::Entity::delete_by_id(upload_uuid)
-        .exec(&state.db)
+    let pipeline = state.pipeline.lock().await;
+    pipeline
+        .abort_multipart_upload(upload.id)
         .await
-        .ok();
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
     Ok(StatusCode::NO_CONTENT.into_response())
 }
@@ -1,26 +1,36 @@
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
-use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::Deserialize;
 
 use crate::db::entities::{bucket, object};
+use crate::s3::conditional;
 use crate::s3::error::S3Error;
+use crate::s3::identity::{self, Principal};
+use crate::s3::policy;
+use crate::s3::sse;
+use crate::s3::tagging;
 use crate::s3::xml;
+use crate::storage::hasher::{compute_checksum, Checksum, ChecksumAlgorithm};
+use crate::storage::pipeline::DeleteOutcome;
 use crate::AppState;
 
 /// PUT /{bucket}/{key..} — Upload object
 pub async fn put_object(
     State(state): State<AppState>,
     Path((bucket_name, key)): Path<(String, String)>,
+    Extension(principal): Extension<Principal>,
     headers: HeaderMap,
     body: axum::body::Bytes,
 ) -> Result<Response, S3Error> {
     // Check for copy source header (CopyObject)
     if let Some(copy_source) = headers.get("x-amz-copy-source") {
-        return copy_object(state, &bucket_name, &key, copy_source, &headers).await;
+        return copy_object(state, &principal, &bucket_name, &key, copy_source, &headers).await;
     }
 
     let bucket = bucket::Entity::find()
@@ -58,28 +68,53 @@ pub async fn put_object(
         Some(serde_json::Value::Object(user_metadata))
     };
 
-    // Upload via storage pipeline
+    let tags = tagging::tags_from_header(&headers)?;
+    let checksum = request_checksum(&headers, &body, None)?;
+    let encryption = sse::customer_key(&headers)?;
+
+    // Upload via storage pipeline; preconditions are checked under the lock
     let pipeline = state.pipeline.lock().await;
+    conditional::check_write_preconditions(&state.db, &headers, bucket.id, &key).await?;
     let obj = pipeline
-        .upload(bucket.id, &key, &body, &content_type, metadata_json)
+        .upload(
+            bucket.id,
+            &key,
+            &body,
+            &content_type,
+            metadata_json,
+            stored_headers(&headers),
+            tags,
+            checksum,
+            encryption.as_ref(),
+        )
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    Ok((
+    let mut response = (
         StatusCode::OK,
         [
             ("ETag", obj.etag.as_str()),
             ("x-amz-request-id", &uuid::Uuid::new_v4().to_string()),
         ],
     )
-        .into_response())
+        .into_response();
+    set_version_header(&mut response, &bucket, &obj.version_id);
+    set_checksum_header(&mut response, &obj);
+    sse::set_response_headers(&mut response, obj.sse_customer_key_md5.as_deref());
+    Ok(response)
 }
 
 /// GET /{bucket}/{key..} — Download object
 pub async fn get_object(
     State(state): State<AppState>,
     Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+    Query(overrides): Query<ResponseOverrides>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
 ) -> Result<Response, S3Error> {
+    check_response_overrides(&overrides, &principal)?;
+
     let bucket = bucket::Entity::find()
         .filter(bucket::Column::Name.eq(&bucket_name))
         .one(&state.db)
@@ -87,18 +122,15 @@ pub async fn get_object(
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    let obj = object::Entity::find()
-        .filter(object::Column::BucketId.eq(bucket.id))
-        .filter(object::Column::Key.eq(&key))
-        .one(&state.db)
-        .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
-        .ok_or_else(|| S3Error::NoSuchKey(format!("Object '{}' not found", key)))?;
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+    let encryption = sse::customer_key(&headers)?;
+    sse::check_key(obj.sse_customer_key_md5.as_deref(), encryption.as_ref())?;
+    conditional::check_read_preconditions(&headers, &obj)?;
 
     // Download via storage pipeline
     let pipeline = state.pipeline.lock().await;
     let data = pipeline
-        .download(obj.id)
+        .download(obj.id, encryption.as_ref())
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
@@ -115,6 +147,19 @@ pub async fn get_object(
         )
         .header("x-amz-request-id", uuid::Uuid::new_v4().to_string());
 
+    if bucket.versioning.is_some() {
+        response = response.header("x-amz-version-id", &obj.version_id);
+    }
+    if let Some(count) = tagging::tag_count(&obj) {
+        response = response.header("x-amz-tagging-count", count.to_string());
+    }
+    if checksum_mode_enabled(&headers) {
+        if let Some((name, value)) = checksum_header(&obj) {
+            response = response.header(name, value);
+        }
+    }
+    response = add_stored_headers(response, &obj);
+
     // Add user metadata headers
     if let Some(ref metadata) = obj.metadata {
         if let Some(map) = metadata.as_object() {
@@ -126,15 +171,20 @@ pub async fn get_object(
         }
     }
 
-    response
+    let mut response = response
         .body(Body::from(data))
-        .map_err(|e| S3Error::InternalError(e.to_string()))
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+    apply_response_overrides(&mut response, &overrides)?;
+    sse::set_response_headers(&mut response, obj.sse_customer_key_md5.as_deref());
+    Ok(response)
 }
 
 /// HEAD /{bucket}/{key..} — Object metadata
 pub async fn head_object(
     State(state): State<AppState>,
     Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+    headers: HeaderMap,
 ) -> Result<Response, S3Error> {
     let bucket = bucket::Entity::find()
         .filter(bucket::Column::Name.eq(&bucket_name))
@@ -143,13 +193,12 @@ pub async fn head_object(
         .map_err(|e| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    let obj = object::Entity::find()
-        .filter(object::Column::BucketId.eq(bucket.id))
-        .filter(object::Column::Key.eq(&key))
-        .one(&state.db)
-        .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
-        .ok_or_else(|| S3Error::NoSuchKey(format!("Object '{}' not found", key)))?;
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+    sse::check_key(
+        obj.sse_customer_key_md5.as_deref(),
+        sse::customer_key(&headers)?.as_ref(),
+    )?;
+    conditional::check_read_preconditions(&headers, &obj)?;
 
     let mut response = Response::builder()
         .status(StatusCode::OK)
@@ -165,6 +214,19 @@ pub async fn head_object(
         .header("Accept-Ranges", "bytes")
         .header("x-amz-request-id", uuid::Uuid::new_v4().to_string());
 
+    if bucket.versioning.is_some() {
+        response = response.header("x-amz-version-id", &obj.version_id);
+    }
+    if let Some(count) = tagging::tag_count(&obj) {
+        response = response.header("x-amz-tagging-count", count.to_string());
+    }
+    if checksum_mode_enabled(&headers) {
+        if let Some((name, value)) = checksum_header(&obj) {
+            response = response.header(name, value);
+        }
+    }
+    response = add_stored_headers(response, &obj);
+
     // Add user metadata headers
     if let Some(ref metadata) = obj.metadata {
         if let Some(map) = metadata.as_object() {
@@ -176,15 +238,19 @@ pub async fn head_object(
         }
     }
 
-    response
+    let mut response = response
         .body(Body::empty())
-        .map_err(|e| S3Error::InternalError(e.to_string()))
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+    sse::set_response_headers(&mut response, obj.sse_customer_key_md5.as_deref());
+    Ok(response)
 }
 
-/// DELETE /{bucket}/{key..} — Delete object
+/// DELETE /{bucket}/{key..} — Delete object (or one version of it)
 pub async fn delete_object(
     State(state): State<AppState>,
     Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+    headers: HeaderMap,
 ) -> Result<Response, S3Error> {
     let bucket = bucket::Entity::find()
         .filter(bucket::Column::Name.eq(&bucket_name))
@@ -193,51 +259,191 @@ pub async fn delete_object(
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
 
-    // Delete via pipeline (handles draft cleanup)
+    // Delete via pipeline (handles draft cleanup and delete markers)
     let pipeline = state.pipeline.lock().await;
-    pipeline
-        .delete_by_key(bucket.id, &key)
+    conditional::check_delete_precondition(
+        &state.db,
+        &headers,
+        bucket.id,
+        &key,
+        version.version_id.as_deref(),
+    )
+    .await?;
+    let outcome = pipeline
+        .delete_by_key(bucket.id, &key, version.version_id.as_deref())
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    Ok(StatusCode::NO_CONTENT.into_response())
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    if let Some(ref version_id) = outcome.version_id {
+        set_version_header(&mut response, &bucket, version_id);
+    }
+    if outcome.delete_marker {
+        response
+            .headers_mut()
+            .insert("x-amz-delete-marker", HeaderValue::from_static("true"));
+    }
+    Ok(response)
+}
+
+/// POST /{bucket}?delete — Delete multiple objects
+pub async fn delete_objects(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    Extension(principal): Extension<Principal>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let body_str = std::str::from_utf8(&body)
+        .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+
+    let request: xml::DeleteRequest = xml::from_xml(body_str)
+        .map_err(|e| S3Error::MalformedXML(format!("Failed to parse Delete XML: {}", e)))?;
+
+    if request.objects.is_empty() || request.objects.len() > 1000 {
+        return Err(S3Error::MalformedXML(
+            "Delete request must contain between 1 and 1000 objects".to_string(),
+        ));
+    }
+
+    let mut result = xml::DeleteResult {
+        deleted: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    // The middleware can't authorize a batch, so each key is checked here;
+    // denied keys are reported and left out of the batch
+    let mut permitted = Vec::with_capacity(request.objects.len());
+    for requested in request.objects {
+        let action = if requested.version_id.is_some() {
+            "s3:DeleteObjectVersion"
+        } else {
+            "s3:DeleteObject"
+        };
+        let ctx =
+            principal.request_context(action, policy::object_arn(&bucket_name, &requested.key));
+        match identity::check_access(&principal, Some(&bucket), &ctx) {
+            Ok(()) => permitted.push(requested),
+            Err(e) => result.errors.push(xml::DeleteError {
+                key: requested.key,
+                code: e.code().to_string(),
+                message: e.message().to_string(),
+            }),
+        }
+    }
+
+    // Delete everything in one batch so draft expunges are coalesced.
+    // Keys that don't exist count as deleted, as with DeleteObject.
+    let keys: Vec<(String, Option<String>)> = permitted
+        .iter()
+        .map(|o| (o.key.clone(), o.version_id.clone()))
+        .collect();
+    let outcomes = if keys.is_empty() {
+        Vec::new()
+    } else {
+        let pipeline = state.pipeline.lock().await;
+        pipeline
+            .delete_keys(bucket.id, &keys)
+            .await
+            .map_err(|e| S3Error::InternalError(e.to_string()))?
+    };
+
+    for (requested, outcome) in permitted.into_iter().zip(outcomes) {
+        match outcome {
+            Err(e) => result.errors.push(xml::DeleteError {
+                key: requested.key,
+                code: "InternalError".to_string(),
+                message: e.to_string(),
+            }),
+            Ok(_) if request.quiet => {}
+            Ok(outcome) => {
+                result
+                    .deleted
+                    .push(deleted_object(requested.key, requested.version_id, outcome))
+            }
+        }
+    }
+
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response())
+}
+
+/// The `Deleted` entry of a DeleteObjects response for one requested key
+fn deleted_object(
+    key: String,
+    requested_version_id: Option<String>,
+    outcome: DeleteOutcome,
+) -> xml::DeletedObject {
+    match requested_version_id {
+        // Deleted a specific version (which may itself be a delete marker)
+        Some(version_id) => xml::DeletedObject {
+            key,
+            version_id: Some(version_id),
+            delete_marker: outcome.delete_marker.then_some(true),
+            delete_marker_version_id: None,
+        },
+        // Possibly created a delete marker
+        None => xml::DeletedObject {
+            key,
+            version_id: None,
+            delete_marker: outcome.delete_marker.then_some(true),
+            delete_marker_version_id: outcome.version_id,
+        },
+    }
 }
 
 /// Internal: CopyObject (PUT with x-amz-copy-source header)
 async fn copy_object(
     state: AppState,
+    principal: &Principal,
     dest_bucket_name: &str,
     dest_key: &str,
     copy_source: &HeaderValue,
-    _headers: &HeaderMap,
+    headers: &HeaderMap,
 ) -> Result<Response, S3Error> {
-    let source_path = copy_source
-        .to_str()
-        .map_err(|_| S3Error::InvalidArgument("Invalid x-amz-copy-source".to_string()))?;
-
-    // Parse source: /bucket/key or bucket/key
-    let source_path = source_path.strip_prefix('/').unwrap_or(source_path);
-    let (source_bucket_name, source_key) = source_path
-        .split_once('/')
-        .ok_or_else(|| S3Error::InvalidArgument("Invalid copy source format".to_string()))?;
+    let (source_bucket_name, source_key, source_version_id) = parse_copy_source(copy_source)?;
 
     // Find source bucket and object
     let source_bucket = bucket::Entity::find()
-        .filter(bucket::Column::Name.eq(source_bucket_name))
+        .filter(bucket::Column::Name.eq(&source_bucket_name))
         .one(&state.db)
         .await
         .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
         .ok_or_else(|| {
             S3Error::NoSuchBucket(format!("Source bucket '{}' not found", source_bucket_name))
         })?;
+    identity::check_copy_source(
+        principal,
+        &source_bucket,
+        &source_key,
+        source_version_id.as_deref(),
+    )?;
 
-    let source_object = object::Entity::find()
-        .filter(object::Column::BucketId.eq(source_bucket.id))
-        .filter(object::Column::Key.eq(source_key))
-        .one(&state.db)
-        .await
-        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
-        .ok_or_else(|| S3Error::NoSuchKey(format!("Source object '{}' not found", source_key)))?;
+    let source_object = find_object(
+        &state.db,
+        source_bucket.id,
+        &source_key,
+        source_version_id.as_deref(),
+    )
+    .await?;
+    let source_encryption = sse::copy_source_key(headers)?;
+    sse::check_key(
+        source_object.sse_customer_key_md5.as_deref(),
+        source_encryption.as_ref(),
+    )?;
+    let encryption = sse::customer_key(headers)?;
 
     // Find destination bucket
     let dest_bucket = bucket::Entity::find()
@@ -252,10 +458,32 @@ async fn copy_object(
             ))
         })?;
 
+    // Tags are copied from the source unless the request replaces them
+    let tags = match headers
+        .get("x-amz-tagging-directive")
+        .and_then(|v| v.to_str().ok())
+    {
+        None | Some("COPY") => source_object.tags.clone(),
+        Some("REPLACE") => tagging::tags_from_header(headers)?,
+        Some(other) => {
+            return Err(S3Error::InvalidArgument(format!(
+                "Unknown tagging directive '{}'",
+                other
+            )))
+        }
+    };
+
     // Copy via pipeline
     let pipeline = state.pipeline.lock().await;
     let new_obj = pipeline
-        .copy(&source_object, dest_bucket.id, dest_key)
+        .copy(
+            &source_object,
+            source_encryption.as_ref(),
+            dest_bucket.id,
+            dest_key,
+            tags,
+            encryption.as_ref(),
+        )
         .await
         .map_err(|e| S3Error::InternalError(e.to_string()))?;
 
@@ -269,10 +497,560 @@ async fn copy_object(
 
     let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
 
-    Ok((
+    let mut response = (
         StatusCode::OK,
         [("Content-Type", "application/xml")],
         xml_body,
     )
-        .into_response())
+        .into_response();
+    set_version_header(&mut response, &dest_bucket, &new_obj.version_id);
+    if source_bucket.versioning.is_some() {
+        if let Ok(value) = HeaderValue::from_str(&source_object.version_id) {
+            response
+                .headers_mut()
+                .insert("x-amz-copy-source-version-id", value);
+        }
+    }
+    sse::set_response_headers(&mut response, new_obj.sse_customer_key_md5.as_deref());
+    Ok(response)
+}
+
+/// Parse an `x-amz-copy-source` header into (bucket, key, version id).
+/// Accepts `/bucket/key` or `bucket/key`, URL-encoded, with an optional `?versionId=` suffix.
+pub fn parse_copy_source(
+    copy_source: &HeaderValue,
+) -> Result<(String, String, Option<String>), S3Error> {
+    let source_path = copy_source
+        .to_str()
+        .map_err(|_| S3Error::InvalidArgument("Invalid x-amz-copy-source".to_string()))?;
+
+    let (source_path, version_id) = match source_path.split_once('?') {
+        Some((path, query)) => (
+            path,
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("versionId="))
+                .map(str::to_string),
+        ),
+        None => (source_path, None),
+    };
+    let source_path = percent_encoding::percent_decode_str(source_path)
+        .decode_utf8()
+        .map_err(|_| S3Error::InvalidArgument("Invalid x-amz-copy-source".to_string()))?;
+
+    // Parse source: /bucket/key or bucket/key
+    let source_path = source_path.strip_prefix('/').unwrap_or(&source_path);
+    let (source_bucket_name, source_key) = source_path
+        .split_once('/')
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| S3Error::InvalidArgument("Invalid copy source format".to_string()))?;
+
+    Ok((
+        source_bucket_name.to_string(),
+        source_key.to_string(),
+        version_id,
+    ))
+}
+
+/// `response-*` query parameters overriding GetObject response headers
+/// (used by presigned download links)
+#[derive(Debug, Default, Deserialize)]
+pub struct ResponseOverrides {
+    #[serde(rename = "response-content-type")]
+    pub content_type: Option<String>,
+    #[serde(rename = "response-content-language")]
+    pub content_language: Option<String>,
+    #[serde(rename = "response-expires")]
+    pub expires: Option<String>,
+    #[serde(rename = "response-cache-control")]
+    pub cache_control: Option<String>,
+    #[serde(rename = "response-content-disposition")]
+    pub content_disposition: Option<String>,
+    #[serde(rename = "response-content-encoding")]
+    pub content_encoding: Option<String>,
+}
+
+/// Object sub-resource selectors (e.g. `?tagging`)
+#[derive(Debug, Deserialize)]
+pub struct ObjectQuery {
+    pub tagging: Option<String>,
+    pub attributes: Option<String>,
+}
+
+/// Query parameter selecting one version of an object
+#[derive(Debug, Deserialize)]
+pub struct VersionQuery {
+    #[serde(rename = "versionId")]
+    pub version_id: Option<String>,
+}
+
+/// Find the current version of a key, or a specific version of it.
+/// A current delete marker reads as a missing key; a delete marker
+/// requested by version id is not a readable object.
+pub async fn find_object(
+    db: &DatabaseConnection,
+    bucket_id: uuid::Uuid,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<object::Model, S3Error> {
+    let mut query = object::Entity::find()
+        .filter(object::Column::BucketId.eq(bucket_id))
+        .filter(object::Column::Key.eq(key))
+        .filter(object::Column::DeletedAt.is_null());
+    query = match version_id {
+        Some(version_id) => query.filter(object::Column::VersionId.eq(version_id)),
+        None => query.filter(object::Column::IsLatest.eq(true)),
+    };
+
+    let obj = query
+        .one(db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+
+    match (obj, version_id) {
+        (Some(obj), _) if !obj.is_delete_marker => Ok(obj),
+        (Some(_), Some(_)) => Err(S3Error::MethodNotAllowed(
+            "The specified method is not allowed against this resource.".to_string(),
+        )),
+        (None, Some(version_id)) => Err(S3Error::NoSuchVersion(format!(
+            "Version '{}' of object '{}' not found",
+            version_id, key
+        ))),
+        _ => Err(S3Error::NoSuchKey(format!("Object '{}' not found", key))),
+    }
+}
+
+/// Set `x-amz-version-id` for objects in buckets that have versioning configured
+pub fn set_version_header(response: &mut Response, bucket: &bucket::Model, version_id: &str) {
+    if bucket.versioning.is_none() {
+        return;
+    }
+    if let Ok(value) = HeaderValue::from_str(version_id) {
+        response.headers_mut().insert("x-amz-version-id", value);
+    }
+}
+
+/// GET /{bucket}/{key..}?attributes — Return the attributes listed in `x-amz-object-attributes`
+pub async fn get_object_attributes(
+    State(state): State<AppState>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+    headers: HeaderMap,
+) -> Result<Response, S3Error> {
+    let requested: Vec<&str> = headers
+        .get("x-amz-object-attributes")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if requested.is_empty() {
+        return Err(S3Error::InvalidArgument(
+            "The x-amz-object-attributes header specifying the attributes to be retrieved is \
+             either missing or empty"
+                .to_string(),
+        ));
+    }
+    if let Some(unknown) = requested.iter().find(|a| {
+        !matches!(
+            **a,
+            "ETag" | "Checksum" | "ObjectParts" | "StorageClass" | "ObjectSize"
+        )
+    }) {
+        return Err(S3Error::InvalidArgument(format!(
+            "Invalid attribute name specified: {}",
+            unknown
+        )));
+    }
+
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+    sse::check_key(
+        obj.sse_customer_key_md5.as_deref(),
+        sse::customer_key(&headers)?.as_ref(),
+    )?;
+
+    let wants = |attribute: &str| requested.contains(&attribute);
+    let checksum =
+        Checksum::from_stored(obj.checksum_algorithm.as_deref(), obj.checksum.as_deref());
+    // Multipart ETags end in "-<part count>"
+    let parts_count = obj
+        .etag
+        .trim_matches('"')
+        .rsplit_once('-')
+        .and_then(|(_, count)| count.parse().ok());
+
+    let result = xml::GetObjectAttributesResponse {
+        etag: wants("ETag").then(|| obj.etag.trim_matches('"').to_string()),
+        checksum: checksum.filter(|_| wants("Checksum")).map(|c| {
+            let mut xml_checksum = xml::ObjectChecksum::default();
+            let field = match c.algorithm {
+                ChecksumAlgorithm::Crc32 => &mut xml_checksum.crc32,
+                ChecksumAlgorithm::Crc32c => &mut xml_checksum.crc32c,
+                ChecksumAlgorithm::Sha1 => &mut xml_checksum.sha1,
+                ChecksumAlgorithm::Sha256 => &mut xml_checksum.sha256,
+            };
+            *field = Some(c.value);
+            xml_checksum
+        }),
+        object_parts: parts_count
+            .filter(|_| wants("ObjectParts"))
+            .map(|total_parts_count| xml::ObjectParts { total_parts_count }),
+        storage_class: wants("StorageClass").then(|| "STANDARD".to_string()),
+        object_size: wants("ObjectSize").then_some(obj.size),
+    };
+
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let mut response = (
+        StatusCode::OK,
+        [
+            ("Content-Type", "application/xml".to_string()),
+            (
+                "Last-Modified",
+                obj.updated_at
+                    .format("%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            ),
+        ],
+        xml_body,
+    )
+        .into_response();
+    set_version_header(&mut response, &bucket, &obj.version_id);
+    Ok(response)
+}
+
+/// Checksum algorithm named by `x-amz-sdk-checksum-algorithm` or `x-amz-checksum-algorithm`
+pub fn requested_checksum_algorithm(
+    headers: &HeaderMap,
+) -> Result<Option<ChecksumAlgorithm>, S3Error> {
+    let Some(name) = headers
+        .get("x-amz-sdk-checksum-algorithm")
+        .or_else(|| headers.get("x-amz-checksum-algorithm"))
+    else {
+        return Ok(None);
+    };
+    name.to_str()
+        .ok()
+        .and_then(ChecksumAlgorithm::parse)
+        .map(Some)
+        .ok_or_else(|| {
+            S3Error::InvalidRequest(
+                "Value for x-amz-checksum-algorithm header is invalid".to_string(),
+            )
+        })
+}
+
+/// Checksum to store for an upload body. A supplied `x-amz-checksum-*` value is
+/// verified against the body (`BadDigest` on mismatch); otherwise the checksum
+/// is computed if the request names an algorithm, falling back to `default`.
+pub fn request_checksum(
+    headers: &HeaderMap,
+    body: &[u8],
+    default: Option<ChecksumAlgorithm>,
+) -> Result<Option<Checksum>, S3Error> {
+    let supplied: Vec<ChecksumAlgorithm> = ChecksumAlgorithm::ALL
+        .into_iter()
+        .filter(|a| headers.contains_key(a.header_name()))
+        .collect();
+    if supplied.len() > 1 {
+        return Err(S3Error::InvalidRequest(
+            "Expecting a single x-amz-checksum- header. Multiple checksum Types are not allowed."
+                .to_string(),
+        ));
+    }
+
+    if let Some(&algorithm) = supplied.first() {
+        let value = compute_checksum(algorithm, body);
+        let expected = headers
+            .get(algorithm.header_name())
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if value != expected.trim() {
+            return Err(S3Error::BadDigest(format!(
+                "The {} you specified did not match the calculated checksum.",
+                algorithm.as_str()
+            )));
+        }
+        return Ok(Some(Checksum { algorithm, value }));
+    }
+
+    Ok(requested_checksum_algorithm(headers)?
+        .or(default)
+        .map(|algorithm| Checksum {
+            algorithm,
+            value: compute_checksum(algorithm, body),
+        }))
+}
+
+/// Whether the client asked for checksums in the response (`x-amz-checksum-mode: ENABLED`)
+fn checksum_mode_enabled(headers: &HeaderMap) -> bool {
+    headers
+        .get("x-amz-checksum-mode")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("ENABLED"))
+}
+
+/// The `x-amz-checksum-*` header for an object's stored checksum, if it has one
+pub fn checksum_header(obj: &object::Model) -> Option<(&'static str, String)> {
+    let checksum =
+        Checksum::from_stored(obj.checksum_algorithm.as_deref(), obj.checksum.as_deref())?;
+    Some((checksum.algorithm.header_name(), checksum.value))
+}
+
+/// Echo an object's stored checksum on an upload response
+fn set_checksum_header(response: &mut Response, obj: &object::Model) {
+    if let Some((name, value)) = checksum_header(obj) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+}
+
+/// Standard HTTP headers stored with an object and returned on GET/HEAD
+const STORED_HEADERS: [&str; 6] = [
+    "cache-control",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "expires",
+    "x-amz-website-redirect-location",
+];
+
+/// The standard HTTP headers of an upload request, as stored in `headers` columns
+pub fn stored_headers(headers: &HeaderMap) -> Option<serde_json::Value> {
+    let map: serde_json::Map<String, serde_json::Value> = STORED_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = headers.get(*name)?.to_str().ok()?;
+            Some((
+                name.to_string(),
+                serde_json::Value::String(value.to_string()),
+            ))
+        })
+        .collect();
+    if map.is_empty() {
+        None
+    } else {
+        Some(serde_json::Value::Object(map))
+    }
+}
+
+/// Add an object's stored standard headers to a GET/HEAD response
+fn add_stored_headers(
+    mut response: axum::http::response::Builder,
+    obj: &object::Model,
+) -> axum::http::response::Builder {
+    if let Some(map) = obj.headers.as_ref().and_then(|h| h.as_object()) {
+        for (name, value) in map {
+            if let Some(value) = value.as_str() {
+                response = response.header(name.as_str(), value);
+            }
+        }
+    }
+    response
+}
+
+/// `response-*` overrides are only honored on signed requests, as in S3
+fn check_response_overrides(
+    overrides: &ResponseOverrides,
+    principal: &Principal,
+) -> Result<(), S3Error> {
+    let any = [
+        &overrides.content_type,
+        &overrides.content_language,
+        &overrides.expires,
+        &overrides.cache_control,
+        &overrides.content_disposition,
+        &overrides.content_encoding,
+    ]
+    .iter()
+    .any(|v| v.is_some());
+    if any && principal.is_anonymous {
+        return Err(S3Error::InvalidRequest(
+            "Request specific response headers cannot be used for anonymous GET requests."
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Replace GetObject response headers with the `response-*` query overrides
+fn apply_response_overrides(
+    response: &mut Response,
+    overrides: &ResponseOverrides,
+) -> Result<(), S3Error> {
+    let pairs = [
+        ("content-type", &overrides.content_type),
+        ("content-language", &overrides.content_language),
+        ("expires", &overrides.expires),
+        ("cache-control", &overrides.cache_control),
+        ("content-disposition", &overrides.content_disposition),
+        ("content-encoding", &overrides.content_encoding),
+    ];
+    for (name, value) in pairs {
+        let Some(value) = value else {
+            continue;
+        };
+        let value = HeaderValue::from_str(value).map_err(|_| {
+            S3Error::InvalidArgument(format!("Invalid value for response-{}", name))
+        })?;
+        response.headers_mut().insert(name, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cache-control", "max-age=60".parse().unwrap());
+        headers.insert("content-disposition", "attachment".parse().unwrap());
+        headers.insert("content-encoding", "gzip".parse().unwrap());
+        headers.insert("content-language", "de".parse().unwrap());
+        headers.insert("expires", "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap());
+        headers.insert("x-amz-website-redirect-location", "/new".parse().unwrap());
+        headers.insert("x-amz-meta-owner", "ops".parse().unwrap());
+        headers.insert("content-type", "text/plain".parse().unwrap());
+
+        let stored = stored_headers(&headers).unwrap();
+        let stored = stored.as_object().unwrap();
+        assert_eq!(stored.len(), STORED_HEADERS.len());
+        assert_eq!(stored["content-encoding"], "gzip");
+        assert_eq!(stored["x-amz-website-redirect-location"], "/new");
+        assert!(stored_headers(&HeaderMap::new()).is_none());
+
+        // Stored headers come back on GET/HEAD
+        let obj = object::Model {
+            headers: Some(serde_json::Value::Object(stored.clone())),
+            ..stored_model()
+        };
+        let response = add_stored_headers(Response::builder(), &obj)
+            .body(Body::empty())
+            .unwrap();
+        for name in STORED_HEADERS {
+            assert_eq!(response.headers()[name], headers[name]);
+        }
+    }
+
+    #[test]
+    fn test_response_overrides() {
+        let overrides = ResponseOverrides {
+            content_type: Some("application/pdf".to_string()),
+            content_disposition: Some("attachment; filename=\"a.pdf\"".to_string()),
+            ..Default::default()
+        };
+        let mut response = Response::builder()
+            .header("content-type", "text/plain")
+            .header("content-language", "de")
+            .body(Body::empty())
+            .unwrap();
+        apply_response_overrides(&mut response, &overrides).unwrap();
+        assert_eq!(response.headers()["content-type"], "application/pdf");
+        assert_eq!(
+            response.headers()["content-disposition"],
+            "attachment; filename=\"a.pdf\""
+        );
+        // Headers without an override are left alone
+        assert_eq!(response.headers()["content-language"], "de");
+
+        let invalid = ResponseOverrides {
+            expires: Some("bad\nvalue".to_string()),
+            ..Default::default()
+        };
+        assert!(apply_response_overrides(&mut response, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_response_overrides_need_signature() {
+        let overrides = ResponseOverrides {
+            cache_control: Some("no-cache".to_string()),
+            ..Default::default()
+        };
+        let anonymous = Principal::anonymous();
+        let signed = Principal {
+            is_anonymous: false,
+            ..Principal::anonymous()
+        };
+        assert!(check_response_overrides(&overrides, &signed).is_ok());
+        assert!(matches!(
+            check_response_overrides(&overrides, &anonymous),
+            Err(S3Error::InvalidRequest(_))
+        ));
+        assert!(check_response_overrides(&ResponseOverrides::default(), &anonymous).is_ok());
+    }
+
+    fn stored_model() -> object::Model {
+        let now = chrono::Utc::now();
+        object::Model {
+            id: uuid::Uuid::new_v4(),
+            bucket_id: uuid::Uuid::nil(),
+            key: "a".to_string(),
+            size: 0,
+            etag: String::new(),
+            content_type: String::new(),
+            metadata: None,
+            chunk_count: 0,
+            created_at: now,
+            updated_at: now,
+            version_id: "null".to_string(),
+            is_latest: true,
+            is_delete_marker: false,
+            deleted_at: None,
+            tags: None,
+            checksum_algorithm: None,
+            checksum: None,
+            headers: None,
+            sse_customer_key_md5: None,
+        }
+    }
+
+    #[test]
+    fn test_deleted_object() {
+        // Deleting a key in a versioned bucket creates a delete marker
+        let marker = DeleteOutcome {
+            version_id: Some("v2".to_string()),
+            delete_marker: true,
+        };
+        let deleted = deleted_object("a".to_string(), None, marker);
+        assert_eq!(deleted.version_id, None);
+        assert_eq!(deleted.delete_marker, Some(true));
+        assert_eq!(deleted.delete_marker_version_id.as_deref(), Some("v2"));
+
+        // Deleting a key in an unversioned bucket removes it outright
+        let deleted = deleted_object("a".to_string(), None, DeleteOutcome::default());
+        assert_eq!(deleted.delete_marker, None);
+        assert_eq!(deleted.delete_marker_version_id, None);
+
+        // Deleting a specific version echoes it back, flagging delete markers
+        let version = DeleteOutcome {
+            version_id: Some("v1".to_string()),
+            delete_marker: false,
+        };
+        let deleted = deleted_object("a".to_string(), Some("v1".to_string()), version);
+        assert_eq!(deleted.version_id.as_deref(), Some("v1"));
+        assert_eq!(deleted.delete_marker, None);
+        assert_eq!(deleted.delete_marker_version_id, None);
+
+        let marker = DeleteOutcome {
+            version_id: Some("v2".to_string()),
+            delete_marker: true,
+        };
+        let deleted = deleted_object("a".to_string(), Some("v2".to_string()), marker);
+        assert_eq!(deleted.version_id.as_deref(), Some("v2"));
+        assert_eq!(deleted.delete_marker, Some(true));
+        assert_eq!(deleted.delete_marker_version_id, None);
+    }
 }
@@ -0,0 +1,676 @@
+use axum::http::Method;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::s3::bucket::BUCKET_SUBRESOURCES;
+use crate::s3::error::S3Error;
+
+/// Policy language versions accepted in `Version`
+const POLICY_VERSIONS: [&str; 2] = ["2012-10-17", "2008-10-17"];
+
+/// Condition operators the evaluator understands
+const CONDITION_OPERATORS: [&str; 7] = [
+    "StringEquals",
+    "StringNotEquals",
+    "StringEqualsIgnoreCase",
+    "StringLike",
+    "StringNotLike",
+    "IpAddress",
+    "NotIpAddress",
+];
+
+/// An IAM-style policy document: a bucket policy (statements name a
+/// `Principal`) or a user's identity policy (statements apply to the user)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct Policy {
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Accepted for compatibility, not used in evaluation
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub id: Option<String>,
+    #[serde(rename = "Statement")]
+    pub statements: OneOrMany<Statement>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct Statement {
+    /// Accepted for compatibility, not used in evaluation
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub sid: Option<String>,
+    pub effect: Effect,
+    #[serde(default)]
+    pub principal: Option<PrincipalSpec>,
+    pub action: OneOrMany<String>,
+    pub resource: OneOrMany<String>,
+    /// Operator → condition key → values
+    #[serde(default)]
+    pub condition: HashMap<String, HashMap<String, OneOrMany<String>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// `"Principal": "*"` or `"Principal": {"AWS": [...]}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PrincipalSpec {
+    Wildcard(String),
+    Aws {
+        #[serde(rename = "AWS")]
+        aws: OneOrMany<String>,
+    },
+}
+
+/// Policy fields that take either a single value or a list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(value) => std::slice::from_ref(value),
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+/// Outcome of evaluating one policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny,
+    /// No statement applies
+    NotApplicable,
+}
+
+/// What a request is trying to do, as seen by policy evaluation
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub action: String,
+    pub resource: String,
+    pub user_id: String,
+    pub username: String,
+    pub source_ip: Option<IpAddr>,
+    /// `prefix` and `delimiter` of listing requests (`s3:prefix`, `s3:delimiter`)
+    pub prefix: Option<String>,
+    pub delimiter: Option<String>,
+    /// Unsigned request: only `"*"` principals match, and it has no user name or id
+    pub is_anonymous: bool,
+}
+
+impl RequestContext {
+    /// Value of a condition key, if the request has one
+    fn value(&self, key: &str) -> Option<String> {
+        match key.to_ascii_lowercase().as_str() {
+            "aws:sourceip" => self.source_ip.map(|ip| ip.to_string()),
+            "aws:username" if !self.is_anonymous => Some(self.username.clone()),
+            "aws:userid" if !self.is_anonymous => Some(self.user_id.clone()),
+            "s3:prefix" => self.prefix.clone(),
+            "s3:delimiter" => self.delimiter.clone(),
+            _ => None,
+        }
+    }
+
+    /// Expand `${aws:username}` and `${aws:userid}` policy variables
+    fn substitute(&self, value: &str) -> String {
+        value
+            .replace("${aws:username}", &self.username)
+            .replace("${aws:userid}", &self.user_id)
+    }
+}
+
+impl Policy {
+    /// Parse and validate a policy document. `bucket` is the bucket a bucket
+    /// policy is attached to (its statements need a `Principal` and may only
+    /// name that bucket's resources); `None` for identity policies, whose
+    /// statements may not have a `Principal`.
+    pub fn parse(document: &[u8], bucket: Option<&str>) -> Result<Policy, S3Error> {
+        let policy: Policy = serde_json::from_slice(document)
+            .map_err(|e| malformed(&format!("Invalid policy document: {}", e)))?;
+
+        if let Some(ref version) = policy.version {
+            if !POLICY_VERSIONS.contains(&version.as_str()) {
+                return Err(malformed("The policy must contain a valid version string"));
+            }
+        }
+        if policy.statements.as_slice().is_empty() {
+            return Err(malformed("Missing required field Statement"));
+        }
+
+        for statement in policy.statements.as_slice() {
+            match (bucket, &statement.principal) {
+                (Some(_), None) => return Err(malformed("Missing required field Principal")),
+                (None, Some(_)) => {
+                    return Err(malformed(
+                        "Identity policies cannot have a Principal element",
+                    ))
+                }
+                (_, Some(PrincipalSpec::Wildcard(p))) if p != "*" => {
+                    return Err(malformed("Invalid principal in policy"))
+                }
+                _ => {}
+            }
+
+            let actions = statement.action.as_slice();
+            if actions.is_empty()
+                || actions
+                    .iter()
+                    .any(|a| a != "*" && !a.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("s3:")))
+            {
+                return Err(malformed("Policy has invalid action"));
+            }
+
+            let resources = statement.resource.as_slice();
+            let valid_resource = |r: &String| match bucket {
+                Some(bucket) => {
+                    let arn = bucket_arn(bucket);
+                    *r == arn || r.starts_with(&format!("{}/", arn))
+                }
+                None => r == "*" || r.starts_with("arn:aws:s3:::"),
+            };
+            if resources.is_empty() || !resources.iter().all(valid_resource) {
+                return Err(malformed("Policy has invalid resource"));
+            }
+
+            for (operator, conditions) in &statement.condition {
+                if !CONDITION_OPERATORS.contains(&operator.as_str()) {
+                    return Err(malformed(&format!(
+                        "Policy has an unsupported condition operator '{}'",
+                        operator
+                    )));
+                }
+                if operator.ends_with("IpAddress") {
+                    let all_valid = conditions
+                        .values()
+                        .flat_map(|values| values.as_slice())
+                        .all(|cidr| parse_cidr(cidr).is_some());
+                    if !all_valid {
+                        return Err(malformed("Invalid IP address in policy condition"));
+                    }
+                }
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Load a policy stored by [`Policy::parse`]
+    pub fn from_stored(value: &serde_json::Value) -> Result<Policy, S3Error> {
+        serde_json::from_value(value.clone())
+            .map_err(|e| S3Error::InternalError(format!("Stored policy is invalid: {}", e)))
+    }
+
+    /// Any matching Deny wins; otherwise Allow if a statement allows
+    pub fn evaluate(&self, ctx: &RequestContext) -> Decision {
+        let mut decision = Decision::NotApplicable;
+        for statement in self.statements.as_slice() {
+            if !statement.applies_to(ctx) {
+                continue;
+            }
+            match statement.effect {
+                Effect::Deny => return Decision::Deny,
+                Effect::Allow => decision = Decision::Allow,
+            }
+        }
+        decision
+    }
+}
+
+impl Statement {
+    fn applies_to(&self, ctx: &RequestContext) -> bool {
+        let principal_matches = match &self.principal {
+            None => true,
+            Some(PrincipalSpec::Wildcard(_)) => true,
+            Some(PrincipalSpec::Aws { aws }) => aws.as_slice().iter().any(|p| {
+                p == "*"
+                    || (!ctx.is_anonymous
+                        && (*p == ctx.username
+                            || *p == ctx.user_id
+                            || p.ends_with(&format!(":user/{}", ctx.username))))
+            }),
+        };
+
+        principal_matches
+            && self.action.as_slice().iter().any(|pattern| {
+                wildcard_match(
+                    &pattern.to_ascii_lowercase(),
+                    &ctx.action.to_ascii_lowercase(),
+                )
+            })
+            && self
+                .resource
+                .as_slice()
+                .iter()
+                .any(|pattern| wildcard_match(&ctx.substitute(pattern), &ctx.resource))
+            && self.condition.iter().all(|(operator, conditions)| {
+                conditions.iter().all(|(key, values)| {
+                    let values: Vec<String> = values
+                        .as_slice()
+                        .iter()
+                        .map(|v| ctx.substitute(v))
+                        .collect();
+                    condition_holds(operator, ctx.value(key).as_deref(), &values)
+                })
+            })
+    }
+}
+
+/// Evaluate one condition; a key the request doesn't have only satisfies negated operators
+fn condition_holds(operator: &str, actual: Option<&str>, values: &[String]) -> bool {
+    let ip = || actual.and_then(|a| a.parse::<IpAddr>().ok());
+    match operator {
+        "StringEquals" => actual.is_some_and(|a| values.iter().any(|v| v == a)),
+        "StringNotEquals" => actual.is_none_or(|a| values.iter().all(|v| v != a)),
+        "StringEqualsIgnoreCase" => {
+            actual.is_some_and(|a| values.iter().any(|v| v.eq_ignore_ascii_case(a)))
+        }
+        "StringLike" => actual.is_some_and(|a| values.iter().any(|v| wildcard_match(v, a))),
+        "StringNotLike" => actual.is_none_or(|a| values.iter().all(|v| !wildcard_match(v, a))),
+        "IpAddress" => ip().is_some_and(|ip| values.iter().any(|v| ip_in_cidr(ip, v))),
+        "NotIpAddress" => ip().is_none_or(|ip| values.iter().all(|v| !ip_in_cidr(ip, v))),
+        _ => false,
+    }
+}
+
+/// Match `value` against a pattern where `*` is any run of characters and `?` any one
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    // Position of the last `*` and the value position it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, v));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Parse `10.0.0.0/8`, `2001:db8::/32` or a bare address (a /32 or /128)
+fn parse_cidr(cidr: &str) -> Option<(IpAddr, u32)> {
+    let (address, prefix_len) = match cidr.split_once('/') {
+        Some((address, len)) => (address, Some(len.parse::<u32>().ok()?)),
+        None => (cidr, None),
+    };
+    let address: IpAddr = address.trim().parse().ok()?;
+    let max_len = if address.is_ipv4() { 32 } else { 128 };
+    let prefix_len = prefix_len.unwrap_or(max_len);
+    (prefix_len <= max_len).then_some((address, prefix_len))
+}
+
+fn ip_in_cidr(ip: IpAddr, cidr: &str) -> bool {
+    let Some((network, prefix_len)) = parse_cidr(cidr) else {
+        return false;
+    };
+    match (ip.to_canonical(), network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix_len).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// The S3 action of a request that is one action on one resource. `None` for
+/// DeleteObjects (`POST /{bucket}?delete`), whose keys are checked one by one,
+/// and browser uploads (`POST /{bucket}`), whose key is in the form.
+pub fn request_action(
+    method: &Method,
+    has_bucket: bool,
+    has_key: bool,
+    params: &[(String, String)],
+) -> Option<&'static str> {
+    let has = |name: &str| params.iter().any(|(k, _)| k == name);
+
+    let action = if !has_bucket {
+        "s3:ListAllMyBuckets"
+    } else if !has_key {
+        match *method {
+            Method::PUT if has("lifecycle") => "s3:PutLifecycleConfiguration",
+            Method::PUT if has("versioning") => "s3:PutBucketVersioning",
+            Method::PUT if has("policy") => "s3:PutBucketPolicy",
+            Method::PUT if has("acl") => "s3:PutBucketAcl",
+            Method::PUT if has("cors") => "s3:PutBucketCORS",
+            Method::PUT => "s3:CreateBucket",
+            Method::DELETE if has("lifecycle") => "s3:PutLifecycleConfiguration",
+            Method::DELETE if has("policy") => "s3:DeleteBucketPolicy",
+            Method::DELETE if has("cors") => "s3:PutBucketCORS",
+            Method::DELETE => "s3:DeleteBucket",
+            Method::POST => return None,
+            Method::GET if has("lifecycle") => "s3:GetLifecycleConfiguration",
+            Method::GET if has("versioning") => "s3:GetBucketVersioning",
+            Method::GET if has("policy") => "s3:GetBucketPolicy",
+            Method::GET if has("acl") => "s3:GetBucketAcl",
+            Method::GET if has("location") => "s3:GetBucketLocation",
+            Method::GET if has("cors") => "s3:GetBucketCORS",
+            Method::GET if has("versions") => "s3:ListBucketVersions",
+            Method::GET if has("uploads") => "s3:ListBucketMultipartUploads",
+            _ => "s3:ListBucket",
+        }
+    } else {
+        match *method {
+            Method::GET if has("tagging") => "s3:GetObjectTagging",
+            Method::GET if has("attributes") => "s3:GetObjectAttributes",
+            Method::GET | Method::HEAD if has("versionId") => "s3:GetObjectVersion",
+            Method::PUT if has("tagging") => "s3:PutObjectTagging",
+            Method::DELETE if has("tagging") => "s3:DeleteObjectTagging",
+            Method::DELETE if has("uploadId") => "s3:AbortMultipartUpload",
+            Method::DELETE if has("versionId") => "s3:DeleteObjectVersion",
+            Method::DELETE => "s3:DeleteObject",
+            Method::PUT | Method::POST => "s3:PutObject",
+            _ => "s3:GetObject",
+        }
+    };
+    Some(action)
+}
+
+/// Reject requests the router would dispatch to a different handler than the
+/// action [`request_action`] names for them; checked before authorization
+pub fn check_dispatchable(
+    method: &Method,
+    has_key: bool,
+    params: &[(String, String)],
+) -> Result<(), S3Error> {
+    let has = |name: &str| params.iter().any(|(k, _)| k == name);
+
+    // ListParts has no handler; the request would be served as GetObject
+    if has_key && *method == Method::GET && has("uploadId") {
+        return Err(S3Error::NotImplemented(
+            "ListParts is not supported".to_string(),
+        ));
+    }
+    // The dispatchers and request_action could pick different subresources
+    let subresources = BUCKET_SUBRESOURCES.iter().filter(|name| has(name)).count();
+    if !has_key && subresources > 1 {
+        return Err(S3Error::InvalidRequest(
+            "A request may name only one bucket subresource".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn bucket_arn(bucket: &str) -> String {
+    format!("arn:aws:s3:::{}", bucket)
+}
+
+pub fn object_arn(bucket: &str, key: &str) -> String {
+    format!("arn:aws:s3:::{}/{}", bucket, key)
+}
+
+fn malformed(message: &str) -> S3Error {
+    S3Error::MalformedPolicy(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(action: &str, resource: &str) -> RequestContext {
+        RequestContext {
+            action: action.to_string(),
+            resource: resource.to_string(),
+            user_id: "5f0c2c4e-0000-0000-0000-000000000000".to_string(),
+            username: "restore-job".to_string(),
+            source_ip: Some("10.1.2.3".parse().unwrap()),
+            prefix: None,
+            delimiter: None,
+            is_anonymous: false,
+        }
+    }
+
+    #[test]
+    fn test_anonymous_principal() {
+        let document = br#"{
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Principal": {"AWS": ["anonymous", "arn:aws:iam::000000000000:user/anonymous"]},
+                    "Action": "s3:GetObject",
+                    "Resource": "arn:aws:s3:::site/private/*"
+                },
+                {
+                    "Effect": "Allow",
+                    "Principal": {"AWS": "*"},
+                    "Action": "s3:GetObject",
+                    "Resource": "arn:aws:s3:::site/public/*"
+                }
+            ]
+        }"#;
+        let policy = Policy::parse(document, Some("site")).unwrap();
+
+        let anonymous = |resource: &str| RequestContext {
+            username: "anonymous".to_string(),
+            user_id: String::new(),
+            source_ip: None,
+            is_anonymous: true,
+            ..context("s3:GetObject", resource)
+        };
+        assert_eq!(
+            policy.evaluate(&anonymous("arn:aws:s3:::site/public/a")),
+            Decision::Allow
+        );
+        assert_eq!(
+            policy.evaluate(&anonymous("arn:aws:s3:::site/private/a")),
+            Decision::NotApplicable
+        );
+        assert_eq!(
+            anonymous("arn:aws:s3:::site/public/a").value("aws:username"),
+            None
+        );
+    }
+
+    fn params(query: &str) -> Vec<(String, String)> {
+        crate::s3::auth::query_params(query)
+    }
+
+    #[test]
+    fn test_list_parts_is_not_dispatched_as_get_object() {
+        let list_parts = params("uploadId=abc");
+        assert!(matches!(
+            check_dispatchable(&Method::GET, true, &list_parts),
+            Err(S3Error::NotImplemented(_))
+        ));
+        // Handled: UploadPart and AbortMultipartUpload
+        let upload_part = params("partNumber=1&uploadId=abc");
+        assert!(check_dispatchable(&Method::PUT, true, &upload_part).is_ok());
+        assert_eq!(
+            request_action(&Method::PUT, true, true, &upload_part),
+            Some("s3:PutObject")
+        );
+        assert!(check_dispatchable(&Method::DELETE, true, &list_parts).is_ok());
+        assert_eq!(
+            request_action(&Method::DELETE, true, true, &list_parts),
+            Some("s3:AbortMultipartUpload")
+        );
+        assert!(check_dispatchable(&Method::GET, true, &params("versionId=v1")).is_ok());
+    }
+
+    #[test]
+    fn test_one_bucket_subresource_per_request() {
+        for method in [Method::GET, Method::PUT, Method::DELETE] {
+            assert!(matches!(
+                check_dispatchable(&method, false, &params("acl&policy")),
+                Err(S3Error::InvalidRequest(_))
+            ));
+        }
+        assert!(check_dispatchable(&Method::GET, false, &params("acl")).is_ok());
+        assert!(check_dispatchable(&Method::GET, false, &params("acl&acl")).is_ok());
+        assert!(check_dispatchable(&Method::GET, false, &params("list-type=2&prefix=a")).is_ok());
+        assert!(check_dispatchable(&Method::POST, false, &params("delete")).is_ok());
+        // Object requests have their own subresources
+        assert!(check_dispatchable(&Method::GET, true, &params("acl&policy")).is_ok());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match(
+            "arn:aws:s3:::backups/*",
+            "arn:aws:s3:::backups/a/b"
+        ));
+        assert!(wildcard_match("s3:get*", "s3:getobject"));
+        assert!(wildcard_match("a?c*", "abcdef"));
+        assert!(wildcard_match("*b*b", "abxbb"));
+        assert!(!wildcard_match(
+            "arn:aws:s3:::backups/*",
+            "arn:aws:s3:::backups"
+        ));
+        assert!(!wildcard_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn test_ip_in_cidr() {
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        assert!(ip_in_cidr(ip, "10.0.0.0/8"));
+        assert!(ip_in_cidr(ip, "10.1.2.3"));
+        assert!(ip_in_cidr(ip, "0.0.0.0/0"));
+        assert!(!ip_in_cidr(ip, "192.168.0.0/16"));
+        assert!(ip_in_cidr("::ffff:10.1.2.3".parse().unwrap(), "10.0.0.0/8"));
+        assert!(ip_in_cidr("2001:db8::1".parse().unwrap(), "2001:db8::/32"));
+        assert!(parse_cidr("10.0.0.0/33").is_none());
+    }
+
+    #[test]
+    fn test_bucket_policy() {
+        let document = br#"{
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Principal": {"AWS": ["arn:aws:iam::000000000000:user/restore-job"]},
+                    "Action": ["s3:GetObject", "s3:ListBucket"],
+                    "Resource": ["arn:aws:s3:::backups", "arn:aws:s3:::backups/*"],
+                    "Condition": {"IpAddress": {"aws:SourceIp": "10.0.0.0/8"}}
+                },
+                {
+                    "Effect": "Deny",
+                    "Principal": "*",
+                    "Action": "s3:*",
+                    "Resource": "arn:aws:s3:::backups/secret/*"
+                }
+            ]
+        }"#;
+        let policy = Policy::parse(document, Some("backups")).unwrap();
+
+        let get = context("s3:GetObject", "arn:aws:s3:::backups/db.tar");
+        assert_eq!(policy.evaluate(&get), Decision::Allow);
+
+        let put = context("s3:PutObject", "arn:aws:s3:::backups/db.tar");
+        assert_eq!(policy.evaluate(&put), Decision::NotApplicable);
+
+        let secret = context("s3:GetObject", "arn:aws:s3:::backups/secret/key");
+        assert_eq!(policy.evaluate(&secret), Decision::Deny);
+
+        let mut elsewhere = get.clone();
+        elsewhere.source_ip = Some("192.168.1.1".parse().unwrap());
+        assert_eq!(policy.evaluate(&elsewhere), Decision::NotApplicable);
+
+        // Bucket policies may only name their own bucket, and need a Principal
+        assert!(Policy::parse(document, Some("other")).is_err());
+        let no_principal =
+            br#"{"Statement": {"Effect": "Allow", "Action": "s3:*", "Resource": "*"}}"#;
+        assert!(Policy::parse(no_principal, Some("backups")).is_err());
+    }
+
+    #[test]
+    fn test_prefix_scoped_identity_policy() {
+        let document = br#"{
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Action": "s3:ListBucket",
+                    "Resource": "arn:aws:s3:::shared",
+                    "Condition": {"StringLike": {"s3:prefix": "${aws:username}/*"}}
+                },
+                {
+                    "Effect": "Allow",
+                    "Action": ["s3:GetObject", "s3:PutObject"],
+                    "Resource": "arn:aws:s3:::shared/${aws:username}/*"
+                }
+            ]
+        }"#;
+        let policy = Policy::parse(document, None).unwrap();
+
+        let put = context("s3:PutObject", "arn:aws:s3:::shared/restore-job/a.txt");
+        assert_eq!(policy.evaluate(&put), Decision::Allow);
+        let other = context("s3:PutObject", "arn:aws:s3:::shared/billing/a.txt");
+        assert_eq!(policy.evaluate(&other), Decision::NotApplicable);
+
+        let mut list = context("s3:ListBucket", "arn:aws:s3:::shared");
+        assert_eq!(policy.evaluate(&list), Decision::NotApplicable);
+        list.prefix = Some("restore-job/2024/".to_string());
+        assert_eq!(policy.evaluate(&list), Decision::Allow);
+
+        // Unsupported elements and operators are rejected rather than ignored
+        let not_action =
+            br#"{"Statement": {"Effect": "Allow", "NotAction": "s3:*", "Resource": "*"}}"#;
+        assert!(Policy::parse(not_action, None).is_err());
+        let bool_condition = br#"{"Statement": {"Effect": "Allow", "Action": "s3:*",
+            "Resource": "*", "Condition": {"Bool": {"aws:SecureTransport": "true"}}}}"#;
+        assert!(Policy::parse(bool_condition, None).is_err());
+    }
+
+    #[test]
+    fn test_request_action() {
+        let params = |names: &[&str]| -> Vec<(String, String)> {
+            names
+                .iter()
+                .map(|n| (n.to_string(), String::new()))
+                .collect()
+        };
+        let action = |method: Method, has_key: bool, names: &[&str]| {
+            request_action(&method, true, has_key, &params(names))
+        };
+        assert_eq!(
+            request_action(&Method::GET, false, false, &[]),
+            Some("s3:ListAllMyBuckets")
+        );
+        assert_eq!(
+            action(Method::GET, false, &["list-type"]),
+            Some("s3:ListBucket")
+        );
+        assert_eq!(
+            action(Method::PUT, false, &["policy"]),
+            Some("s3:PutBucketPolicy")
+        );
+        assert_eq!(action(Method::POST, false, &["delete"]), None);
+        assert_eq!(action(Method::HEAD, true, &[]), Some("s3:GetObject"));
+        assert_eq!(
+            action(Method::PUT, true, &["partNumber", "uploadId"]),
+            Some("s3:PutObject")
+        );
+        assert_eq!(
+            action(Method::DELETE, true, &["uploadId"]),
+            Some("s3:AbortMultipartUpload")
+        );
+    }
+}
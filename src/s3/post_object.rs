@@ -0,0 +1,558 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::db::entities::bucket;
+use crate::s3::auth;
+use crate::s3::error::S3Error;
+use crate::s3::identity::{self, Principal};
+use crate::s3::object::{set_version_header, stored_headers};
+use crate::s3::policy;
+use crate::s3::sse;
+use crate::s3::tagging;
+use crate::s3::xml;
+use crate::AppState;
+
+/// Form fields that a POST policy doesn't have to mention
+const UNCHECKED_FIELDS: [&str; 4] = ["x-amz-signature", "policy", "file", "bucket"];
+
+/// A parsed multipart/form-data upload
+#[derive(Debug)]
+pub struct PostForm {
+    /// Fields before the file, by lowercased name (S3 field names are case-insensitive)
+    pub fields: HashMap<String, String>,
+    pub file: Bytes,
+    pub filename: Option<String>,
+    pub file_content_type: Option<String>,
+}
+
+/// The policy document a browser form is signed with
+#[derive(Debug, Deserialize)]
+struct PostPolicy {
+    expiration: String,
+    #[serde(default)]
+    conditions: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Condition {
+    /// Field must equal the value exactly
+    Eq(String, String),
+    /// Field must start with the value (any value if empty)
+    StartsWith(String, String),
+    /// Uploaded file size must be within the range, inclusive
+    ContentLengthRange(u64, u64),
+}
+
+/// Whether a request is a browser form upload (POST Object). These carry their
+/// signature in the form, so the auth middleware leaves them to [`post_object`].
+pub fn is_post_object(method: &Method, path: &str, content_type: &str) -> bool {
+    let path = path.trim_matches('/');
+    method == Method::POST && !path.is_empty() && !path.contains('/') && is_form_data(content_type)
+}
+
+/// Whether a request body is multipart/form-data
+pub fn is_form_data(content_type: &str) -> bool {
+    content_type
+        .to_ascii_lowercase()
+        .starts_with("multipart/form-data")
+}
+
+/// POST /{bucket} with multipart/form-data — Browser-based upload (POST Object)
+pub async fn post_object(
+    State(state): State<AppState>,
+    Path(bucket_name): Path<String>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let form = parse_form(content_type, &body)?;
+    let mut fields = form.fields;
+    fields.insert("bucket".to_string(), bucket_name.clone());
+
+    let key = fields
+        .get("key")
+        .ok_or_else(|| {
+            S3Error::InvalidArgument("Bucket POST must contain a field named 'key'".to_string())
+        })?
+        .replace("${filename}", form.filename.as_deref().unwrap_or(""));
+    if key.is_empty() {
+        return Err(S3Error::InvalidArgument(
+            "User key must have a length greater than 0".to_string(),
+        ));
+    }
+
+    let principal = match fields.get("policy") {
+        Some(encoded) => {
+            let signer = authenticate(&state, &fields, encoded).await?;
+            let document = BASE64_STANDARD.decode(encoded.trim()).map_err(|_| {
+                S3Error::InvalidPolicyDocument(
+                    "Invalid Policy: Invalid Base64 encoding".to_string(),
+                )
+            })?;
+            check_policy(&document, &fields, form.file.len() as u64, Utc::now())?;
+            Principal {
+                source_ip: principal.source_ip,
+                ..signer
+            }
+        }
+        None => principal,
+    };
+
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let ctx = principal.request_context("s3:PutObject", policy::object_arn(&bucket_name, &key));
+    identity::check_access(&principal, Some(&bucket), &ctx)?;
+
+    // Form fields stand in for the headers of a PutObject
+    let object_content_type = fields
+        .get("content-type")
+        .cloned()
+        .or(form.file_content_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let metadata: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .filter_map(|(name, value)| {
+            let meta_key = name.strip_prefix("x-amz-meta-")?;
+            Some((
+                meta_key.to_string(),
+                serde_json::Value::String(value.clone()),
+            ))
+        })
+        .collect();
+    let metadata_json = (!metadata.is_empty()).then_some(serde_json::Value::Object(metadata));
+    let mut field_headers = HeaderMap::new();
+    for (name, value) in &fields {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            field_headers.insert(name, value);
+        }
+    }
+    let tags = match fields.get("tagging") {
+        Some(tagging_xml) => {
+            let tagging: xml::Tagging = xml::from_xml(tagging_xml).map_err(|e| {
+                S3Error::MalformedXML(format!("Failed to parse Tagging XML: {}", e))
+            })?;
+            tagging::validate_tags(&tagging.tag_set.tags)?;
+            tagging::tags_to_json(&tagging.tag_set.tags)
+        }
+        None => None,
+    };
+    let encryption = sse::customer_key(&field_headers)?;
+
+    let pipeline = state.pipeline.lock().await;
+    let obj = pipeline
+        .upload(
+            bucket.id,
+            &key,
+            &form.file,
+            &object_content_type,
+            metadata_json,
+            stored_headers(&field_headers),
+            tags,
+            None,
+            encryption.as_ref(),
+        )
+        .await
+        .map_err(|e| S3Error::InternalError(e.to_string()))?;
+    drop(pipeline);
+
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("localhost");
+    let location = format!(
+        "http://{}/{}/{}",
+        host,
+        bucket_name,
+        utf8_percent_encode(&key, NON_ALPHANUMERIC)
+    );
+
+    let mut response = if let Some(redirect) = fields.get("success_action_redirect") {
+        let separator = if redirect.contains('?') { '&' } else { '?' };
+        let target = format!(
+            "{}{}bucket={}&key={}&etag={}",
+            redirect,
+            separator,
+            utf8_percent_encode(&bucket_name, NON_ALPHANUMERIC),
+            utf8_percent_encode(&key, NON_ALPHANUMERIC),
+            utf8_percent_encode(&obj.etag, NON_ALPHANUMERIC)
+        );
+        (StatusCode::SEE_OTHER, [(header::LOCATION, target)]).into_response()
+    } else {
+        match fields.get("success_action_status").map(String::as_str) {
+            Some("200") => StatusCode::OK.into_response(),
+            Some("201") => {
+                let result = xml::PostResponse {
+                    location: location.clone(),
+                    bucket: bucket_name.clone(),
+                    key: key.clone(),
+                    etag: obj.etag.clone(),
+                };
+                let xml_body =
+                    xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+                (
+                    StatusCode::CREATED,
+                    [("Content-Type", "application/xml")],
+                    xml_body,
+                )
+                    .into_response()
+            }
+            _ => StatusCode::NO_CONTENT.into_response(),
+        }
+    };
+
+    let response_headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&obj.etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if !response_headers.contains_key(header::LOCATION) {
+        if let Ok(value) = HeaderValue::from_str(&location) {
+            response_headers.insert(header::LOCATION, value);
+        }
+    }
+    set_version_header(&mut response, &bucket, &obj.version_id);
+    sse::set_response_headers(&mut response, obj.sse_customer_key_md5.as_deref());
+    Ok(response)
+}
+
+/// Check the SigV4 signature over the base64 policy and resolve its signer
+async fn authenticate(
+    state: &AppState,
+    fields: &HashMap<String, String>,
+    encoded_policy: &str,
+) -> Result<Principal, S3Error> {
+    let field = |name: &str| {
+        fields.get(name).map(String::as_str).ok_or_else(|| {
+            S3Error::InvalidArgument(format!("Bucket POST must contain a field named '{}'", name))
+        })
+    };
+
+    if field("x-amz-algorithm")? != "AWS4-HMAC-SHA256" {
+        return Err(S3Error::InvalidArgument(
+            "Only the AWS4-HMAC-SHA256 algorithm is supported".to_string(),
+        ));
+    }
+    let scope: Vec<&str> = field("x-amz-credential")?.splitn(5, '/').collect();
+    let [access_key_id, date, region, service, _] = scope[..] else {
+        return Err(S3Error::InvalidArgument(
+            "Invalid x-amz-credential".to_string(),
+        ));
+    };
+    let signature = field("x-amz-signature")?;
+
+    let credential = identity::lookup_credential(state, access_key_id).await?;
+    identity::check_session_token(
+        &credential,
+        fields.get("x-amz-security-token").map(String::as_str),
+    )?;
+
+    let signing_key =
+        auth::derive_signing_key(&credential.secret_access_key, date, region, service);
+    if auth::compute_signature(&signing_key, encoded_policy) != signature {
+        return Err(S3Error::SignatureDoesNotMatch(
+            "The request signature we calculated does not match the signature you provided"
+                .to_string(),
+        ));
+    }
+    Ok(credential.principal)
+}
+
+/// Split a multipart/form-data body into its fields and the file. Fields after
+/// the file are ignored, as by S3.
+pub fn parse_form(content_type: &str, body: &Bytes) -> Result<PostForm, S3Error> {
+    let malformed = || {
+        S3Error::MalformedPOSTRequest(
+            "The body of your POST request is not well-formed multipart/form-data".to_string(),
+        )
+    };
+
+    let boundary = content_type
+        .split(';')
+        .filter_map(|param| param.trim().split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim_matches('"'))
+        .filter(|boundary| !boundary.is_empty())
+        .ok_or_else(malformed)?;
+    let delimiter = format!("\r\n--{}", boundary);
+
+    // The first delimiter may be at the very start, without the leading CRLF
+    let mut pos =
+        find(body, &delimiter.as_bytes()[2..], 0).ok_or_else(malformed)? + delimiter.len() - 2;
+
+    let mut fields = HashMap::new();
+    loop {
+        if body[pos..].starts_with(b"--") {
+            return Err(S3Error::InvalidArgument(
+                "POST requires exactly one file upload per request".to_string(),
+            ));
+        }
+        pos = find(body, b"\r\n", pos).ok_or_else(malformed)? + 2;
+        let end = find(body, delimiter.as_bytes(), pos).ok_or_else(malformed)?;
+        let headers_end = find(&body[..end], b"\r\n\r\n", pos).ok_or_else(malformed)?;
+
+        let part_headers = std::str::from_utf8(&body[pos..headers_end]).map_err(|_| malformed())?;
+        let mut name = None;
+        let mut filename = None;
+        let mut part_content_type = None;
+        for line in part_headers.split("\r\n") {
+            let Some((header_name, value)) = line.split_once(':') else {
+                continue;
+            };
+            if header_name
+                .trim()
+                .eq_ignore_ascii_case("content-disposition")
+            {
+                for param in value.split(';').skip(1) {
+                    match param.trim().split_once('=') {
+                        Some(("name", v)) => name = Some(v.trim_matches('"').to_string()),
+                        Some(("filename", v)) => filename = Some(v.trim_matches('"').to_string()),
+                        _ => {}
+                    }
+                }
+            } else if header_name.trim().eq_ignore_ascii_case("content-type") {
+                part_content_type = Some(value.trim().to_string());
+            }
+        }
+
+        let name = name.ok_or_else(malformed)?.to_ascii_lowercase();
+        let value = body.slice(headers_end + 4..end);
+        if name == "file" {
+            return Ok(PostForm {
+                fields,
+                file: value,
+                filename,
+                file_content_type: part_content_type,
+            });
+        }
+        fields.insert(name, String::from_utf8_lossy(&value).into_owned());
+        pos = end + delimiter.len();
+    }
+}
+
+/// Check a decoded POST policy against the form: it must not have expired,
+/// every condition must hold, and every field must be covered by a condition
+fn check_policy(
+    document: &[u8],
+    fields: &HashMap<String, String>,
+    file_size: u64,
+    now: DateTime<Utc>,
+) -> Result<(), S3Error> {
+    let invalid =
+        |message: &str| S3Error::InvalidPolicyDocument(format!("Invalid Policy: {}", message));
+
+    let policy: PostPolicy =
+        serde_json::from_slice(document).map_err(|e| invalid(&e.to_string()))?;
+    let expiration = DateTime::parse_from_rfc3339(&policy.expiration)
+        .map_err(|_| invalid("Invalid 'expiration' value"))?;
+    if expiration < now {
+        return Err(S3Error::AccessDenied(
+            "Invalid according to Policy: Policy expired".to_string(),
+        ));
+    }
+
+    let conditions = policy
+        .conditions
+        .iter()
+        .map(parse_condition)
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("Invalid condition"))?;
+
+    for (condition, raw) in conditions.iter().zip(&policy.conditions) {
+        let holds = match condition {
+            Condition::Eq(field, value) => fields.get(field) == Some(value),
+            Condition::StartsWith(field, prefix) => {
+                fields.get(field).map_or(prefix.is_empty(), |value| {
+                    value.starts_with(prefix.as_str())
+                })
+            }
+            Condition::ContentLengthRange(min, max) => {
+                if file_size < *min {
+                    return Err(S3Error::EntityTooSmall(
+                        "Your proposed upload is smaller than the minimum allowed size".to_string(),
+                    ));
+                }
+                if file_size > *max {
+                    return Err(S3Error::EntityTooLarge(
+                        "Your proposed upload exceeds the maximum allowed size".to_string(),
+                    ));
+                }
+                true
+            }
+        };
+        if !holds {
+            return Err(S3Error::AccessDenied(format!(
+                "Invalid according to Policy: Policy Condition failed: {}",
+                raw
+            )));
+        }
+    }
+
+    for name in fields.keys() {
+        let covered = conditions.iter().any(|condition| match condition {
+            Condition::Eq(field, _) | Condition::StartsWith(field, _) => field == name,
+            Condition::ContentLengthRange(..) => false,
+        });
+        if !covered && !UNCHECKED_FIELDS.contains(&name.as_str()) && !name.starts_with("x-ignore-")
+        {
+            return Err(S3Error::AccessDenied(format!(
+                "Invalid according to Policy: Extra input fields: {}",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `{"field": "value"}`, `["eq" | "starts-with", "$field", "value"]`
+/// or `["content-length-range", min, max]`
+fn parse_condition(value: &serde_json::Value) -> Option<Condition> {
+    let field_name = |name: &str| name.trim_start_matches('$').to_ascii_lowercase();
+    let number = |value: &serde_json::Value| match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+
+    match value {
+        serde_json::Value::Object(map) if map.len() == 1 => {
+            let (field, value) = map.iter().next()?;
+            Some(Condition::Eq(
+                field_name(field),
+                value.as_str()?.to_string(),
+            ))
+        }
+        serde_json::Value::Array(items) if items.len() == 3 => {
+            let op = items[0].as_str()?.to_ascii_lowercase();
+            match op.as_str() {
+                "eq" => Some(Condition::Eq(
+                    field_name(items[1].as_str()?),
+                    items[2].as_str()?.to_string(),
+                )),
+                "starts-with" => Some(Condition::StartsWith(
+                    field_name(items[1].as_str()?),
+                    items[2].as_str()?.to_string(),
+                )),
+                "content-length-range" => Some(Condition::ContentLengthRange(
+                    number(&items[1])?,
+                    number(&items[2])?,
+                )),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Position of `needle` in `haystack` at or after `from`
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| i + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_form() {
+        let body = Bytes::from_static(
+            b"--XyZ\r\n\
+              Content-Disposition: form-data; name=\"Key\"\r\n\r\n\
+              uploads/${filename}\r\n\
+              --XyZ\r\n\
+              Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+              Content-Type: text/plain\r\n\r\n\
+              hello\r\nworld\r\n\
+              --XyZ\r\n\
+              Content-Disposition: form-data; name=\"ignored\"\r\n\r\n\
+              after the file\r\n\
+              --XyZ--\r\n",
+        );
+        let form = parse_form("multipart/form-data; boundary=XyZ", &body).unwrap();
+        assert_eq!(form.fields.len(), 1);
+        assert_eq!(form.fields["key"], "uploads/${filename}");
+        assert_eq!(&form.file[..], b"hello\r\nworld");
+        assert_eq!(form.filename.as_deref(), Some("a.txt"));
+        assert_eq!(form.file_content_type.as_deref(), Some("text/plain"));
+
+        let no_file = Bytes::from_static(
+            b"--XyZ\r\nContent-Disposition: form-data; name=\"key\"\r\n\r\nk\r\n--XyZ--\r\n",
+        );
+        assert!(parse_form("multipart/form-data; boundary=XyZ", &no_file).is_err());
+    }
+
+    #[test]
+    fn test_check_policy() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let document = br#"{
+            "expiration": "2026-01-02T00:00:00.000Z",
+            "conditions": [
+                {"bucket": "photos"},
+                ["starts-with", "$key", "uploads/"],
+                ["content-length-range", 1, 1024],
+                {"x-amz-credential": "AKID/20260101/us-east-1/s3/aws4_request"},
+                {"x-amz-algorithm": "AWS4-HMAC-SHA256"}
+            ]
+        }"#;
+        let mut fields: HashMap<String, String> = [
+            ("bucket", "photos"),
+            ("key", "uploads/cat.jpg"),
+            (
+                "x-amz-credential",
+                "AKID/20260101/us-east-1/s3/aws4_request",
+            ),
+            ("x-amz-algorithm", "AWS4-HMAC-SHA256"),
+            ("x-amz-signature", "abc"),
+            ("policy", "..."),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert!(check_policy(document, &fields, 100, now).is_ok());
+        assert!(matches!(
+            check_policy(document, &fields, 2048, now),
+            Err(S3Error::EntityTooLarge(_))
+        ));
+        assert!(check_policy(document, &fields, 100, now + chrono::Duration::days(2)).is_err());
+
+        fields.insert("key".to_string(), "elsewhere/cat.jpg".to_string());
+        assert!(check_policy(document, &fields, 100, now).is_err());
+
+        // Fields the policy doesn't mention are rejected
+        fields.insert("key".to_string(), "uploads/cat.jpg".to_string());
+        fields.insert("acl".to_string(), "public-read".to_string());
+        assert!(check_policy(document, &fields, 100, now).is_err());
+        fields.insert("x-ignore-acl".to_string(), "public-read".to_string());
+        fields.remove("acl");
+        assert!(check_policy(document, &fields, 100, now).is_ok());
+    }
+}
@@ -1,16 +1,25 @@
 use axum::{
-    extract::{Path, Query, Request},
-    http::Method,
+    extract::{OriginalUri, Path, Query, RawQuery, Request, State},
+    http::{header, HeaderMap, Uri},
     middleware::{self, Next},
     response::Response,
     routing::{delete, get, head, post, put},
     Router,
 };
-use std::sync::Arc;
 
-use crate::s3::{auth, bucket, multipart, object, sts};
+use crate::s3::{
+    acl, admin, auth, bucket, cors, identity, multipart, object, post_object, sts, tagging,
+};
 use crate::AppState;
 
+/// Largest request body the S3 API accepts (5 GiB). Middleware that buffers
+/// the raw body has to enforce it itself: `DefaultBodyLimit` only applies to
+/// extractors.
+pub const MAX_BODY_SIZE: usize = 5 * 1024 * 1024 * 1024;
+
+/// Largest STS form body (1 MiB)
+pub const STS_MAX_BODY_SIZE: usize = 1024 * 1024;
+
 /// Simple request logger middleware
 async fn log_middleware(req: Request, next: Next) -> Response {
     let method = req.method().clone();
@@ -21,89 +30,316 @@ async fn log_middleware(req: Request, next: Next) -> Response {
     res
 }
 
+/// Rewrite virtual-hosted-style requests (`{bucket}.{domain}/{key}`) to path
+/// style. This has to wrap the whole router, since routing needs the rewritten
+/// path; the URI the client signed stays available as `OriginalUri`.
+pub async fn virtual_host_middleware(
+    State(domain): State<Option<String>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let bucket = domain
+        .as_deref()
+        .and_then(|domain| virtual_host_bucket(request.headers(), domain));
+    if let Some(bucket) = bucket {
+        let original = request.uri().clone();
+        let rewritten = match original.query() {
+            Some(query) => format!("/{}{}?{}", bucket, original.path(), query),
+            None => format!("/{}{}", bucket, original.path()),
+        };
+        if let Ok(uri) = rewritten.parse::<Uri>() {
+            request.extensions_mut().insert(OriginalUri(original));
+            *request.uri_mut() = uri;
+        }
+    }
+    next.run(request).await
+}
+
+/// Bucket named by a `Host` header of the form `{bucket}.{domain}[:port]`
+fn virtual_host_bucket(headers: &HeaderMap, domain: &str) -> Option<String> {
+    let host = headers
+        .get(header::HOST)?
+        .to_str()
+        .ok()?
+        .to_ascii_lowercase();
+    let host = host
+        .rsplit_once(':')
+        .map_or(host.as_str(), |(name, _)| name);
+    let bucket = host
+        .strip_suffix(domain.to_ascii_lowercase().as_str())?
+        .strip_suffix('.')?;
+    (!bucket.is_empty()).then(|| bucket.to_string())
+}
+
 /// Build the S3-compatible API router
 pub fn build_router(state: AppState) -> Router {
-    // STS endpoint (POST /) does NOT go through the SigV4 auth middleware
-    // because the console uses STS *to obtain* credentials.
+    // STS endpoint (POST /) does NOT go through the SigV4 auth middleware:
+    // it authenticates its own form body (see auth::authenticate_sts).
     let sts_router = Router::new()
         .route("/", post(sts::assume_role))
         .layer(middleware::from_fn(log_middleware))
-        .layer(axum::extract::DefaultBodyLimit::max(1024 * 1024))
+        .layer(axum::extract::DefaultBodyLimit::max(STS_MAX_BODY_SIZE))
         .with_state(state.clone());
 
     // All other S3 routes go through SigV4 auth
     let s3_router = Router::new()
         // Service-level operations
         .route("/", get(bucket::list_buckets))
+        // Admin operations (bucket names can't start with '_')
+        .route("/_admin/trash", get(admin::list_trash))
+        .route("/_admin/trash/:id/restore", post(admin::restore_trashed))
+        .route("/_admin/users", get(admin::list_users))
+        .route("/_admin/users/:name", post(admin::create_user))
+        .route("/_admin/users/:name/disable", post(admin::disable_user))
+        .route("/_admin/users/:name/enable", post(admin::enable_user))
+        .route("/_admin/users/:name/keys", post(admin::create_access_key))
+        .route(
+            "/_admin/users/:name/policy",
+            get(admin::get_user_policy)
+                .put(admin::put_user_policy)
+                .delete(admin::delete_user_policy),
+        )
+        .route("/_admin/keys/:id", delete(admin::delete_access_key))
+        .route("/_admin/keys/:id/disable", post(admin::disable_access_key))
+        .route("/_admin/keys/:id/enable", post(admin::enable_access_key))
+        .route("/_admin/keys/:id/rotate", post(admin::rotate_access_key))
         // Bucket-level operations
-        .route("/:bucket", put(bucket::create_bucket))
-        .route("/:bucket/", put(bucket::create_bucket))
-        .route("/:bucket", delete(bucket::delete_bucket))
-        .route("/:bucket/", delete(bucket::delete_bucket))
+        .route("/:bucket", put(bucket_put_handler))
+        .route("/:bucket/", put(bucket_put_handler))
+        .route("/:bucket", delete(bucket_delete_handler))
+        .route("/:bucket/", delete(bucket_delete_handler))
         .route("/:bucket", head(bucket::head_bucket))
         .route("/:bucket/", head(bucket::head_bucket))
         .route("/:bucket", get(bucket_or_list_handler))
         .route("/:bucket/", get(bucket_or_list_handler))
+        .route("/:bucket", post(bucket_post_handler))
+        .route("/:bucket/", post(bucket_post_handler))
         // Object-level operations
         .route("/:bucket/*key", put(object_put_handler))
-        .route("/:bucket/*key", get(object::get_object))
+        .route("/:bucket/*key", get(object_get_handler))
         .route("/:bucket/*key", head(object::head_object))
         .route("/:bucket/*key", delete(object_delete_handler))
         .route("/:bucket/*key", post(object_post_handler))
-        // Apply SigV4 auth middleware
-        .layer(middleware::from_fn(auth::auth_middleware))
-        // Inject S3 config into extensions for the auth middleware
-        .layer(axum::Extension(Arc::new(state.config.s3.clone())))
+        // Apply SigV4 auth middleware (credentials are looked up in the config and DB)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::auth_middleware,
+        ))
+        // Answer CORS preflights before auth, and add CORS headers to every
+        // response, errors included
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            cors::cors_middleware,
+        ))
         // Apply logger middleware
         .layer(middleware::from_fn(log_middleware))
         // Increase body limit to 5GB
-        .layer(axum::extract::DefaultBodyLimit::max(5 * 1024 * 1024 * 1024))
+        .layer(axum::extract::DefaultBodyLimit::max(MAX_BODY_SIZE))
         .with_state(state);
 
     // Merge routers — STS routes take priority for POST /
     sts_router.merge(s3_router)
 }
 
-/// GET /{bucket} — dispatches to ListObjectsV2 or other bucket-level GET
+/// GET /{bucket} — dispatches to ListObjects (V1/V2) or other bucket-level GET.
+/// Subresources without a handler here are NotImplemented, never listings.
 async fn bucket_or_list_handler(
     state: axum::extract::State<AppState>,
     path: Path<String>,
     query: Query<bucket::ListObjectsQuery>,
-    request: axum::extract::Request,
+    subresource: Query<bucket::BucketQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<axum::response::Response, crate::s3::error::S3Error> {
+    if subresource.location.is_some() {
+        // GetBucketLocation
+        return bucket::get_bucket_location(state, path).await;
+    }
+    if subresource.lifecycle.is_some() {
+        // GetBucketLifecycleConfiguration
+        return bucket::get_bucket_lifecycle(state, path).await;
+    }
+    if subresource.versioning.is_some() {
+        // GetBucketVersioning
+        return bucket::get_bucket_versioning(state, path).await;
+    }
+    if subresource.acl.is_some() {
+        // GetBucketAcl
+        return acl::get_bucket_acl(state, path).await;
+    }
+    if subresource.policy.is_some() {
+        // GetBucketPolicy
+        return bucket::get_bucket_policy(state, path).await;
+    }
+    if subresource.cors.is_some() {
+        // GetBucketCors
+        return cors::get_bucket_cors(state, path).await;
+    }
+    if subresource.versions.is_some() {
+        // ListObjectVersions
+        return bucket::list_object_versions(state, path, query).await;
+    }
+    bucket::reject_subresources(raw_query.as_deref())?;
+
+    // Otherwise GET /{bucket} is a listing; V2 is opted into with list-type=2
+    if query.list_type.as_deref() == Some("2") {
+        bucket::list_objects_v2(state, path, query).await
+    } else {
+        bucket::list_objects(state, path, query).await
+    }
+}
+
+/// PUT /{bucket} — dispatches to CreateBucket or a bucket configuration PUT
+async fn bucket_put_handler(
+    state: axum::extract::State<AppState>,
+    path: Path<String>,
+    subresource: Query<bucket::BucketQuery>,
+    RawQuery(raw_query): RawQuery,
+    principal: axum::Extension<identity::Principal>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<axum::response::Response, crate::s3::error::S3Error> {
+    if subresource.lifecycle.is_some() {
+        // PutBucketLifecycleConfiguration
+        bucket::put_bucket_lifecycle(state, path, body).await
+    } else if subresource.versioning.is_some() {
+        // PutBucketVersioning
+        bucket::put_bucket_versioning(state, path, body).await
+    } else if subresource.policy.is_some() {
+        // PutBucketPolicy
+        bucket::put_bucket_policy(state, path, body).await
+    } else if subresource.acl.is_some() {
+        // PutBucketAcl
+        acl::put_bucket_acl(state, path, headers, body).await
+    } else if subresource.cors.is_some() {
+        // PutBucketCors
+        cors::put_bucket_cors(state, path, body).await
+    } else {
+        // CreateBucket
+        bucket::reject_subresources(raw_query.as_deref())?;
+        bucket::create_bucket(state, path, principal, headers, body).await
+    }
+}
+
+/// DELETE /{bucket} — dispatches to DeleteBucket or a bucket configuration DELETE
+async fn bucket_delete_handler(
+    state: axum::extract::State<AppState>,
+    path: Path<String>,
+    subresource: Query<bucket::BucketQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<axum::response::Response, crate::s3::error::S3Error> {
+    if subresource.lifecycle.is_some() {
+        // DeleteBucketLifecycle
+        bucket::delete_bucket_lifecycle(state, path).await
+    } else if subresource.policy.is_some() {
+        // DeleteBucketPolicy
+        bucket::delete_bucket_policy(state, path).await
+    } else if subresource.cors.is_some() {
+        // DeleteBucketCors
+        cors::delete_bucket_cors(state, path).await
+    } else {
+        // DeleteBucket
+        bucket::reject_subresources(raw_query.as_deref())?;
+        bucket::delete_bucket(state, path).await
+    }
+}
+
+/// POST /{bucket} — dispatches to DeleteObjects or POST Object (browser upload)
+async fn bucket_post_handler(
+    state: axum::extract::State<AppState>,
+    path: Path<String>,
+    subresource: Query<bucket::BucketQuery>,
+    RawQuery(raw_query): RawQuery,
+    principal: axum::Extension<identity::Principal>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<axum::response::Response, crate::s3::error::S3Error> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if subresource.delete.is_some() {
+        // DeleteObjects
+        object::delete_objects(state, path, principal, body).await
+    } else if post_object::is_form_data(content_type) {
+        // Browser-based upload with a signed policy
+        post_object::post_object(state, path, principal, headers, body).await
+    } else {
+        bucket::reject_subresources(raw_query.as_deref())?;
+        Err(crate::s3::error::S3Error::InvalidRequest(
+            "Invalid POST request".to_string(),
+        ))
+    }
+}
+
+/// GET /{bucket}/{key} — dispatches to GetObject, GetObjectTagging or GetObjectAttributes
+async fn object_get_handler(
+    state: axum::extract::State<AppState>,
+    path: Path<(String, String)>,
+    subresource: Query<object::ObjectQuery>,
+    version: Query<object::VersionQuery>,
+    overrides: Query<object::ResponseOverrides>,
+    principal: axum::Extension<identity::Principal>,
+    headers: axum::http::HeaderMap,
 ) -> Result<axum::response::Response, crate::s3::error::S3Error> {
-    // Always treat GET /{bucket} as ListObjectsV2
-    bucket::list_objects_v2(state, path, query).await
+    if subresource.tagging.is_some() {
+        // GetObjectTagging
+        tagging::get_object_tagging(state, path, version).await
+    } else if subresource.attributes.is_some() {
+        // GetObjectAttributes
+        object::get_object_attributes(state, path, version, headers).await
+    } else {
+        // GetObject
+        object::get_object(state, path, version, overrides, principal, headers).await
+    }
 }
 
-/// PUT /{bucket}/{key} — dispatches to PutObject or UploadPart
+/// PUT /{bucket}/{key} — dispatches to PutObject, PutObjectTagging, UploadPart or UploadPartCopy
+#[allow(clippy::too_many_arguments)]
 async fn object_put_handler(
     state: axum::extract::State<AppState>,
     path: Path<(String, String)>,
     query: Query<multipart::MultipartQuery>,
+    subresource: Query<object::ObjectQuery>,
+    version: Query<object::VersionQuery>,
+    principal: axum::Extension<identity::Principal>,
     headers: axum::http::HeaderMap,
     body: axum::body::Bytes,
 ) -> Result<axum::response::Response, crate::s3::error::S3Error> {
-    if query.part_number.is_some() && query.upload_id.is_some() {
-        // UploadPart
-        multipart::upload_part(state, path, query, body).await
+    if subresource.tagging.is_some() {
+        // PutObjectTagging
+        tagging::put_object_tagging(state, path, version, body).await
+    } else if query.part_number.is_some() && query.upload_id.is_some() {
+        if headers.contains_key("x-amz-copy-source") {
+            // UploadPartCopy
+            multipart::upload_part_copy(state, path, query, principal, headers).await
+        } else {
+            // UploadPart
+            multipart::upload_part(state, path, query, headers, body).await
+        }
     } else {
         // PutObject
-        object::put_object(state, path, headers, body).await
+        object::put_object(state, path, principal, headers, body).await
     }
 }
 
-/// DELETE /{bucket}/{key} — dispatches to DeleteObject or AbortMultipartUpload
+/// DELETE /{bucket}/{key} — dispatches to DeleteObject, DeleteObjectTagging or AbortMultipartUpload
 async fn object_delete_handler(
     state: axum::extract::State<AppState>,
     path: Path<(String, String)>,
     query: Query<multipart::MultipartQuery>,
+    subresource: Query<object::ObjectQuery>,
+    version: Query<object::VersionQuery>,
+    headers: axum::http::HeaderMap,
 ) -> Result<axum::response::Response, crate::s3::error::S3Error> {
-    if query.upload_id.is_some() {
+    if subresource.tagging.is_some() {
+        // DeleteObjectTagging
+        tagging::delete_object_tagging(state, path, version).await
+    } else if query.upload_id.is_some() {
         // AbortMultipartUpload
         multipart::abort_multipart_upload(state, path, query).await
     } else {
         // DeleteObject
-        object::delete_object(state, path).await
+        object::delete_object(state, path, version, headers).await
     }
 }
 
@@ -120,10 +356,53 @@ async fn object_post_handler(
         multipart::create_multipart_upload(state, path, headers).await
     } else if query.upload_id.is_some() {
         // CompleteMultipartUpload
-        multipart::complete_multipart_upload(state, path, query, body).await
+        multipart::complete_multipart_upload(state, path, query, headers, body).await
     } else {
         Err(crate::s3::error::S3Error::InvalidRequest(
             "Invalid POST request".to_string(),
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket_for(host: &str) -> Option<String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, host.parse().unwrap());
+        virtual_host_bucket(&headers, "s3.example.com")
+    }
+
+    #[test]
+    fn test_virtual_host_bucket() {
+        assert_eq!(
+            bucket_for("photos.s3.example.com").as_deref(),
+            Some("photos")
+        );
+        assert_eq!(
+            bucket_for("photos.s3.example.com:9000").as_deref(),
+            Some("photos")
+        );
+        assert_eq!(
+            bucket_for("Photos.S3.Example.com").as_deref(),
+            Some("photos")
+        );
+        assert_eq!(
+            bucket_for("my.dotted.bucket.s3.example.com").as_deref(),
+            Some("my.dotted.bucket")
+        );
+
+        // The bare domain is path style
+        assert_eq!(bucket_for("s3.example.com"), None);
+        assert_eq!(bucket_for("s3.example.com:9000"), None);
+        assert_eq!(bucket_for(".s3.example.com"), None);
+        // Only whole labels match the domain
+        assert_eq!(bucket_for("photos-s3.example.com"), None);
+        assert_eq!(bucket_for("photos.other.com"), None);
+        assert_eq!(
+            virtual_host_bucket(&HeaderMap::new(), "s3.example.com"),
+            None
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::Response;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+
+use crate::s3::error::S3Error;
+use crate::storage::encryption::CustomerKey;
+
+/// The only algorithm SSE-C supports
+const ALGORITHM: &str = "AES256";
+
+const ALGORITHM_HEADER: &str = "x-amz-server-side-encryption-customer-algorithm";
+const KEY_HEADER: &str = "x-amz-server-side-encryption-customer-key";
+const KEY_MD5_HEADER: &str = "x-amz-server-side-encryption-customer-key-md5";
+/// Prefix of the headers giving the key of a copy source
+const COPY_SOURCE_PREFIX: &str = "x-amz-copy-source-";
+
+/// Customer key from the `x-amz-server-side-encryption-customer-*` headers, if any
+pub fn customer_key(headers: &HeaderMap) -> Result<Option<CustomerKey>, S3Error> {
+    parse_key(headers, "")
+}
+
+/// Key of a copy source, from the `x-amz-copy-source-server-side-encryption-customer-*` headers
+pub fn copy_source_key(headers: &HeaderMap) -> Result<Option<CustomerKey>, S3Error> {
+    parse_key(headers, COPY_SOURCE_PREFIX)
+}
+
+fn parse_key(headers: &HeaderMap, prefix: &str) -> Result<Option<CustomerKey>, S3Error> {
+    let header = |name: &str| {
+        headers
+            .get(format!("{}{}", prefix, name))
+            .map(|v| v.to_str().unwrap_or("").trim())
+    };
+    let algorithm = header(ALGORITHM_HEADER);
+    let key = header(KEY_HEADER);
+    let key_md5 = header(KEY_MD5_HEADER);
+    if algorithm.is_none() && key.is_none() && key_md5.is_none() {
+        return Ok(None);
+    }
+
+    if algorithm != Some(ALGORITHM) {
+        return Err(S3Error::InvalidArgument(
+            "The server side encryption algorithm must be AES256 when customer-provided keys \
+             are used"
+                .to_string(),
+        ));
+    }
+    let key = key.ok_or_else(|| {
+        S3Error::InvalidArgument(
+            "Requests specifying Server Side Encryption with Customer provided keys must \
+             provide an appropriate secret key."
+                .to_string(),
+        )
+    })?;
+    let key: [u8; 32] = BASE64_STANDARD
+        .decode(key)
+        .ok()
+        .and_then(|k| k.try_into().ok())
+        .ok_or_else(|| {
+            S3Error::InvalidArgument(
+                "The secret key was invalid for the specified algorithm.".to_string(),
+            )
+        })?;
+
+    let key = CustomerKey::new(&key);
+    if key_md5 != Some(key.key_md5.as_str()) {
+        return Err(S3Error::InvalidArgument(
+            "The calculated MD5 hash of the key did not match the hash that was provided."
+                .to_string(),
+        ));
+    }
+    Ok(Some(key))
+}
+
+/// Check the key a request came with against the one the object is stored under
+/// (`stored_md5`): an SSE-C object can only be read with its own key, and a key
+/// makes no sense for an object stored without one
+pub fn check_key(stored_md5: Option<&str>, key: Option<&CustomerKey>) -> Result<(), S3Error> {
+    match (stored_md5, key) {
+        (None, None) => Ok(()),
+        (Some(_), None) => Err(S3Error::InvalidRequest(
+            "The object was stored using a form of Server Side Encryption. The correct \
+             parameters must be provided to retrieve the object."
+                .to_string(),
+        )),
+        (None, Some(_)) => Err(S3Error::InvalidRequest(
+            "The encryption parameters are not applicable to this object.".to_string(),
+        )),
+        (Some(stored_md5), Some(key)) if stored_md5 == key.key_md5 => Ok(()),
+        (Some(_), Some(_)) => Err(S3Error::AccessDenied(
+            "The provided customer key does not match the key the object was encrypted with"
+                .to_string(),
+        )),
+    }
+}
+
+/// Like [`check_key`], for the parts of a multipart upload: they must come with
+/// the key the upload was initiated with, or with none if it wasn't
+pub fn check_upload_key(
+    stored_md5: Option<&str>,
+    key: Option<&CustomerKey>,
+) -> Result<(), S3Error> {
+    match (stored_md5, key) {
+        (Some(_), None) => Err(S3Error::InvalidRequest(
+            "The multipart upload initiate requested encryption. Subsequent part requests \
+             must include the appropriate encryption parameters."
+                .to_string(),
+        )),
+        (None, Some(_)) => Err(S3Error::InvalidRequest(
+            "The multipart upload initiate did not request encryption. Part requests must \
+             not include encryption parameters."
+                .to_string(),
+        )),
+        _ => check_key(stored_md5, key),
+    }
+}
+
+/// Echo the algorithm and key MD5 on responses about SSE-C data
+pub fn set_response_headers(response: &mut Response, key_md5: Option<&str>) {
+    let Some(key_md5) = key_md5 else {
+        return;
+    };
+    let headers = response.headers_mut();
+    headers.insert(ALGORITHM_HEADER, HeaderValue::from_static(ALGORITHM));
+    if let Ok(value) = HeaderValue::from_str(key_md5) {
+        headers.insert(KEY_MD5_HEADER, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sse_headers(prefix: &str, key: &[u8; 32]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let md5 = CustomerKey::new(key).key_md5;
+        let mut set = |name: &str, value: String| {
+            headers.insert(
+                axum::http::HeaderName::from_bytes(format!("{}{}", prefix, name).as_bytes())
+                    .unwrap(),
+                value.parse().unwrap(),
+            );
+        };
+        set(ALGORITHM_HEADER, ALGORITHM.to_string());
+        set(KEY_HEADER, BASE64_STANDARD.encode(key));
+        set(KEY_MD5_HEADER, md5);
+        headers
+    }
+
+    #[test]
+    fn test_customer_key() {
+        assert!(customer_key(&HeaderMap::new()).unwrap().is_none());
+
+        let key = customer_key(&sse_headers("", &[3u8; 32])).unwrap().unwrap();
+        assert_eq!(key.key_md5, CustomerKey::new(&[3u8; 32]).key_md5);
+        assert!(copy_source_key(&sse_headers("", &[3u8; 32]))
+            .unwrap()
+            .is_none());
+        assert!(
+            copy_source_key(&sse_headers(COPY_SOURCE_PREFIX, &[3u8; 32]))
+                .unwrap()
+                .is_some()
+        );
+
+        let mut headers = sse_headers("", &[3u8; 32]);
+        headers.insert(KEY_MD5_HEADER, "bm90IHRoZSBrZXkgbWQ1IQ==".parse().unwrap());
+        assert!(customer_key(&headers).is_err());
+
+        let mut headers = sse_headers("", &[3u8; 32]);
+        headers.insert(ALGORITHM_HEADER, "aws:kms".parse().unwrap());
+        assert!(customer_key(&headers).is_err());
+
+        let mut headers = sse_headers("", &[3u8; 32]);
+        headers.insert(
+            KEY_HEADER,
+            BASE64_STANDARD.encode([3u8; 16]).parse().unwrap(),
+        );
+        assert!(customer_key(&headers).is_err());
+    }
+
+    #[test]
+    fn test_check_key() {
+        let key = CustomerKey::new(&[5u8; 32]);
+        let other = CustomerKey::new(&[6u8; 32]);
+        assert!(check_key(None, None).is_ok());
+        assert!(check_key(Some(&key.key_md5), Some(&key)).is_ok());
+        assert!(check_key(Some(&key.key_md5), None).is_err());
+        assert!(check_key(None, Some(&key)).is_err());
+        assert!(matches!(
+            check_key(Some(&key.key_md5), Some(&other)),
+            Err(S3Error::AccessDenied(_))
+        ));
+    }
+}
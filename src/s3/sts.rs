@@ -1,107 +1,243 @@
 use axum::{
-    extract::State,
+    extract::{Request, State},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use chrono::{Duration, Utc};
+use percent_encoding::percent_decode_str;
+use sea_orm::{ActiveModelTrait, Set};
 use std::collections::HashMap;
+use uuid::Uuid;
 
+use crate::db::entities::session_credential;
+use crate::s3::auth;
+use crate::s3::error::S3Error;
+use crate::s3::identity::{self, Principal};
+use crate::s3::policy::Policy;
 use crate::AppState;
 
-/// Handle STS AssumeRole requests (POST /)
+/// Shortest lifetime that can be requested with DurationSeconds
+const MIN_DURATION_SECS: u64 = 900;
+
+/// Handle STS requests (POST /): AssumeRole and GetSessionToken.
 /// The MinIO Console uses STS to get temporary credentials before using the S3 API.
-/// We respond with the same static credentials from config, acting as a pass-through STS.
+/// Requests are signed with a permanent key; the credentials handed out expire
+/// and are only accepted together with their session token. AssumeRole may
+/// narrow them further with a session policy.
 pub async fn assume_role(
     State(state): State<AppState>,
-    body: String,
-) -> Response {
-    // Parse form body to get Action
-    let params: HashMap<&str, &str> = body
-        .split('&')
-        .filter_map(|pair| {
-            let mut parts = pair.splitn(2, '=');
-            let k = parts.next()?;
-            let v = parts.next().unwrap_or("");
-            Some((k, v))
-        })
-        .collect();
+    request: Request,
+) -> Result<Response, S3Error> {
+    let (principal, body) = auth::authenticate_sts(&state, request).await?;
+    let params = form_params(&String::from_utf8_lossy(&body));
+
+    let action = params.get("Action").map(String::as_str).unwrap_or("");
+
+    tracing::info!("STS request: Action={} ({})", action, principal.display_name);
+
+    if principal.is_session {
+        return Err(S3Error::AccessDenied(
+            "Temporary credentials cannot be used to request new ones".to_string(),
+        ));
+    }
+
+    let session_policy = match action {
+        "AssumeRole" => params
+            .get("Policy")
+            .map(|document| {
+                Policy::parse(document.as_bytes(), None)?;
+                serde_json::from_str::<serde_json::Value>(document).map_err(|e| {
+                    S3Error::MalformedPolicy(format!("Invalid policy document: {}", e))
+                })
+            })
+            .transpose()?,
+        "GetSessionToken" => None,
+        other => {
+            return Err(S3Error::NotImplemented(format!(
+                "STS action '{}' is not supported",
+                other
+            )))
+        }
+    };
+
+    let config = &state.config.s3;
+    let duration_secs = duration_secs(
+        params.get("DurationSeconds").map(String::as_str),
+        config.sts_default_duration_secs,
+        config.sts_max_duration_secs,
+    )?;
 
-    let action = params.get("Action").copied().unwrap_or("");
+    let session_name = match params.get("RoleSessionName") {
+        Some(name) if valid_session_name(name) => name.clone(),
+        Some(_) => {
+            return Err(S3Error::InvalidArgument(
+                "RoleSessionName must be 2-64 characters of [A-Za-z0-9+=,.@_-]".to_string(),
+            ))
+        }
+        None => "objectmail".to_string(),
+    };
 
-    tracing::info!("STS request: Action={}", action);
+    let session = issue_session(&state, &principal, session_policy, duration_secs).await?;
 
-    // For any STS action (AssumeRole, GetSessionToken, etc.), return static credentials
-    let access_key = &state.config.s3.access_key_id;
-    let secret_key = &state.config.s3.secret_access_key;
-    let expiry = "2099-01-01T00:00:00Z";
+    let credentials = format!(
+        r#"<Credentials>
+      <AccessKeyId>{access_key}</AccessKeyId>
+      <SecretAccessKey>{secret_key}</SecretAccessKey>
+      <SessionToken>{session_token}</SessionToken>
+      <Expiration>{expiry}</Expiration>
+    </Credentials>"#,
+        access_key = session.access_key_id,
+        secret_key = session.secret_access_key,
+        session_token = session.session_token,
+        expiry = session.expires_at.format("%Y-%m-%dT%H:%M:%SZ"),
+    );
+    let request_id = Uuid::new_v4();
 
     let xml = match action {
         "AssumeRole" => format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <AssumeRoleResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
   <AssumeRoleResult>
-    <Credentials>
-      <AccessKeyId>{access_key}</AccessKeyId>
-      <SecretAccessKey>{secret_key}</SecretAccessKey>
-      <SessionToken>objectmail-session-token</SessionToken>
-      <Expiration>{expiry}</Expiration>
-    </Credentials>
+    {credentials}
     <AssumedRoleUser>
-      <Arn>arn:aws:iam::000000000000:assumed-role/objectmail/objectmail</Arn>
-      <AssumedRoleId>objectmail</AssumedRoleId>
+      <Arn>arn:aws:sts::000000000000:assumed-role/objectmail/{session_name}</Arn>
+      <AssumedRoleId>{access_key}:{session_name}</AssumedRoleId>
     </AssumedRoleUser>
   </AssumeRoleResult>
   <ResponseMetadata>
-    <RequestId>00000000-0000-0000-0000-000000000000</RequestId>
+    <RequestId>{request_id}</RequestId>
   </ResponseMetadata>
 </AssumeRoleResponse>"#,
-            access_key = access_key,
-            secret_key = secret_key,
-            expiry = expiry
+            credentials = credentials,
+            session_name = session_name,
+            access_key = session.access_key_id,
+            request_id = request_id
         ),
-        "GetSessionToken" => format!(
+        _ => format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <GetSessionTokenResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
   <GetSessionTokenResult>
-    <Credentials>
-      <AccessKeyId>{access_key}</AccessKeyId>
-      <SecretAccessKey>{secret_key}</SecretAccessKey>
-      <SessionToken>objectmail-session-token</SessionToken>
-      <Expiration>{expiry}</Expiration>
-    </Credentials>
+    {credentials}
   </GetSessionTokenResult>
   <ResponseMetadata>
-    <RequestId>00000000-0000-0000-0000-000000000000</RequestId>
+    <RequestId>{request_id}</RequestId>
   </ResponseMetadata>
 </GetSessionTokenResponse>"#,
-            access_key = access_key,
-            secret_key = secret_key,
-            expiry = expiry
-        ),
-        _ => format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<AssumeRoleResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
-  <AssumeRoleResult>
-    <Credentials>
-      <AccessKeyId>{access_key}</AccessKeyId>
-      <SecretAccessKey>{secret_key}</SecretAccessKey>
-      <SessionToken>objectmail-session-token</SessionToken>
-      <Expiration>{expiry}</Expiration>
-    </Credentials>
-  </AssumeRoleResult>
-  <ResponseMetadata>
-    <RequestId>00000000-0000-0000-0000-000000000000</RequestId>
-  </ResponseMetadata>
-</AssumeRoleResponse>"#,
-            access_key = access_key,
-            secret_key = secret_key,
-            expiry = expiry
+            credentials = credentials,
+            request_id = request_id
         ),
     };
 
-    (
-        StatusCode::OK,
-        [("Content-Type", "text/xml")],
-        xml,
-    )
-        .into_response()
+    Ok((StatusCode::OK, [("Content-Type", "text/xml")], xml).into_response())
+}
+
+/// Store a new set of temporary credentials for `principal`
+async fn issue_session(
+    state: &AppState,
+    principal: &Principal,
+    policy: Option<serde_json::Value>,
+    duration_secs: u64,
+) -> Result<session_credential::Model, S3Error> {
+    let user_id = if principal.is_root {
+        None
+    } else {
+        Some(
+            Uuid::parse_str(&principal.id)
+                .map_err(|e| S3Error::InternalError(e.to_string()))?,
+        )
+    };
+    let now = Utc::now();
+
+    session_credential::ActiveModel {
+        access_key_id: Set(identity::generate_access_key_id()),
+        user_id: Set(user_id),
+        secret_access_key: Set(identity::generate_secret_access_key()),
+        session_token: Set(identity::generate_session_token()),
+        policy: Set(policy),
+        expires_at: Set(now + Duration::seconds(duration_secs as i64)),
+        created_at: Set(now),
+    }
+    .insert(&state.db)
+    .await
+    .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))
+}
+
+/// Lifetime of the credentials: the requested DurationSeconds, which must be
+/// within bounds, or the default (capped at the maximum)
+fn duration_secs(requested: Option<&str>, default: u64, max: u64) -> Result<u64, S3Error> {
+    match requested {
+        None => Ok(default.min(max)),
+        Some(value) => value
+            .parse::<u64>()
+            .ok()
+            .filter(|secs| (MIN_DURATION_SECS..=max).contains(secs))
+            .ok_or_else(|| {
+                S3Error::InvalidArgument(format!(
+                    "DurationSeconds must be between {} and {}",
+                    MIN_DURATION_SECS, max
+                ))
+            }),
+    }
+}
+
+/// Decode an application/x-www-form-urlencoded body
+fn form_params(body: &str) -> HashMap<String, String> {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(k), decode(v))
+        })
+        .collect()
+}
+
+/// RoleSessionName as AWS accepts it, which also keeps it safe to echo in XML
+fn valid_session_name(name: &str) -> bool {
+    (2..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+=,.@_-".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_params() {
+        let params =
+            form_params("Action=AssumeRole&Version=2011-06-15&RoleSessionName=a+b%2Fc&Empty");
+        assert_eq!(params["Action"], "AssumeRole");
+        assert_eq!(params["RoleSessionName"], "a b/c");
+        assert_eq!(params["Empty"], "");
+        assert!(form_params("").is_empty());
+    }
+
+    #[test]
+    fn test_duration_secs() {
+        assert_eq!(duration_secs(None, 3600, 43200).unwrap(), 3600);
+        assert_eq!(duration_secs(None, 3600, 1800).unwrap(), 1800);
+        assert_eq!(duration_secs(Some("900"), 3600, 43200).unwrap(), 900);
+        assert_eq!(duration_secs(Some("43200"), 3600, 43200).unwrap(), 43200);
+        assert!(duration_secs(Some("899"), 3600, 43200).is_err());
+        assert!(duration_secs(Some("43201"), 3600, 43200).is_err());
+        assert!(duration_secs(Some("-1"), 3600, 43200).is_err());
+        assert!(duration_secs(Some("1h"), 3600, 43200).is_err());
+    }
+
+    #[test]
+    fn test_valid_session_name() {
+        assert!(valid_session_name("ci"));
+        assert!(valid_session_name("user@example.com,build=42"));
+        assert!(valid_session_name(&"a".repeat(64)));
+        assert!(!valid_session_name("a"));
+        assert!(!valid_session_name(&"a".repeat(65)));
+        assert!(!valid_session_name("a b"));
+        assert!(!valid_session_name("<name>"));
+    }
 }
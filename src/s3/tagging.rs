@@ -0,0 +1,266 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use percent_encoding::percent_decode_str;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use std::collections::HashSet;
+
+use crate::db::entities::{bucket, object};
+use crate::s3::error::S3Error;
+use crate::s3::object::{find_object, set_version_header, VersionQuery};
+use crate::s3::xml;
+use crate::AppState;
+
+/// Maximum number of tags on one object
+const MAX_TAGS: usize = 10;
+/// Maximum tag key length, in characters
+const MAX_KEY_LENGTH: usize = 128;
+/// Maximum tag value length, in characters
+const MAX_VALUE_LENGTH: usize = 256;
+
+/// GET /{bucket}/{key..}?tagging — Return the object's tag set
+pub async fn get_object_tagging(
+    State(state): State<AppState>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+
+    let result = xml::Tagging {
+        tag_set: xml::TagSet {
+            tags: tags_from_json(obj.tags.as_ref()),
+        },
+    };
+    let xml_body = xml::to_xml(&result).map_err(|e| S3Error::InternalError(e.to_string()))?;
+
+    let mut response = (
+        StatusCode::OK,
+        [("Content-Type", "application/xml")],
+        xml_body,
+    )
+        .into_response();
+    set_version_header(&mut response, &bucket, &obj.version_id);
+    Ok(response)
+}
+
+/// PUT /{bucket}/{key..}?tagging — Replace the object's tag set
+pub async fn put_object_tagging(
+    State(state): State<AppState>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+    body: axum::body::Bytes,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let body_str = std::str::from_utf8(&body)
+        .map_err(|_| S3Error::MalformedXML("Invalid UTF-8 in request body".to_string()))?;
+    let tagging: xml::Tagging = xml::from_xml(body_str)
+        .map_err(|e| S3Error::MalformedXML(format!("Failed to parse Tagging XML: {}", e)))?;
+    validate_tags(&tagging.tag_set.tags)?;
+
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+    let version_id = obj.version_id.clone();
+    update_tags(&state, obj, tags_to_json(&tagging.tag_set.tags)).await?;
+
+    let mut response = StatusCode::OK.into_response();
+    set_version_header(&mut response, &bucket, &version_id);
+    Ok(response)
+}
+
+/// DELETE /{bucket}/{key..}?tagging — Remove all tags from the object
+pub async fn delete_object_tagging(
+    State(state): State<AppState>,
+    Path((bucket_name, key)): Path<(String, String)>,
+    Query(version): Query<VersionQuery>,
+) -> Result<Response, S3Error> {
+    let bucket = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(&bucket_name))
+        .one(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?
+        .ok_or_else(|| S3Error::NoSuchBucket(format!("Bucket '{}' not found", bucket_name)))?;
+
+    let obj = find_object(&state.db, bucket.id, &key, version.version_id.as_deref()).await?;
+    let version_id = obj.version_id.clone();
+    update_tags(&state, obj, None).await?;
+
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    set_version_header(&mut response, &bucket, &version_id);
+    Ok(response)
+}
+
+async fn update_tags(
+    state: &AppState,
+    obj: object::Model,
+    tags: Option<serde_json::Value>,
+) -> Result<(), S3Error> {
+    let mut active: object::ActiveModel = obj.into();
+    active.tags = Set(tags);
+    active
+        .update(&state.db)
+        .await
+        .map_err(|e: sea_orm::DbErr| S3Error::InternalError(e.to_string()))?;
+    Ok(())
+}
+
+/// Parse the `x-amz-tagging` header (URL query encoded, e.g. `k1=v1&k2=v2`)
+/// into the JSON stored in `tags` columns. `None` if absent or empty.
+pub fn tags_from_header(headers: &HeaderMap) -> Result<Option<serde_json::Value>, S3Error> {
+    let Some(value) = headers.get("x-amz-tagging") else {
+        return Ok(None);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| S3Error::InvalidTag("Invalid x-amz-tagging header".to_string()))?;
+
+    let tags = parse_tag_query(value)?;
+    validate_tags(&tags)?;
+    Ok(tags_to_json(&tags))
+}
+
+/// Decode `k1=v1&k2=v2` (percent-encoded, `+` for space); a missing `=` means an empty value
+fn parse_tag_query(query: &str) -> Result<Vec<xml::Tag>, S3Error> {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8()
+            .map(|d| d.into_owned())
+            .map_err(|_| S3Error::InvalidTag("Tags must be valid UTF-8".to_string()))
+    };
+
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok(xml::Tag {
+                key: decode(key)?,
+                value: decode(value)?,
+            })
+        })
+        .collect()
+}
+
+/// Enforce S3's limits: at most 10 tags, unique non-empty keys of up to
+/// 128 characters and values of up to 256 characters
+pub fn validate_tags(tags: &[xml::Tag]) -> Result<(), S3Error> {
+    if tags.len() > MAX_TAGS {
+        return Err(S3Error::InvalidTag(format!(
+            "Object tags cannot be greater than {}",
+            MAX_TAGS
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for tag in tags {
+        if tag.key.is_empty() || tag.key.chars().count() > MAX_KEY_LENGTH {
+            return Err(S3Error::InvalidTag(format!(
+                "The TagKey '{}' you have provided is invalid",
+                tag.key
+            )));
+        }
+        if tag.value.chars().count() > MAX_VALUE_LENGTH {
+            return Err(S3Error::InvalidTag(format!(
+                "The TagValue you have provided for '{}' is too long",
+                tag.key
+            )));
+        }
+        if !seen.insert(tag.key.as_str()) {
+            return Err(S3Error::InvalidTag(
+                "Cannot provide multiple Tags with the same key".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Tags as stored in the DB: a JSON object of key → value (`None` if there are none)
+pub fn tags_to_json(tags: &[xml::Tag]) -> Option<serde_json::Value> {
+    if tags.is_empty() {
+        return None;
+    }
+    let map = tags
+        .iter()
+        .map(|t| (t.key.clone(), serde_json::Value::String(t.value.clone())))
+        .collect();
+    Some(serde_json::Value::Object(map))
+}
+
+/// Tags as stored in the DB, back as a tag list
+pub fn tags_from_json(tags: Option<&serde_json::Value>) -> Vec<xml::Tag> {
+    tags.and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| {
+                    Some(xml::Tag {
+                        key: k.clone(),
+                        value: v.as_str()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Value of the `x-amz-tagging-count` header, if the object has tags
+pub fn tag_count(obj: &object::Model) -> Option<usize> {
+    obj.tags
+        .as_ref()
+        .and_then(|v| v.as_object())
+        .map(|map| map.len())
+        .filter(|count| *count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag_query() {
+        let tags = parse_tag_query("project=blue%20sky&team=a+b&empty=&bare").unwrap();
+        let pairs: Vec<(&str, &str)> = tags
+            .iter()
+            .map(|t| (t.key.as_str(), t.value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("project", "blue sky"),
+                ("team", "a b"),
+                ("empty", ""),
+                ("bare", "")
+            ]
+        );
+        assert!(parse_tag_query("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        let tag = |k: &str, v: &str| xml::Tag {
+            key: k.to_string(),
+            value: v.to_string(),
+        };
+        assert!(validate_tags(&[tag("a", "1"), tag("b", "")]).is_ok());
+        assert!(validate_tags(&[tag("a", "1"), tag("a", "2")]).is_err());
+        assert!(validate_tags(&[tag("", "1")]).is_err());
+        assert!(validate_tags(&[tag(&"k".repeat(129), "1")]).is_err());
+        assert!(validate_tags(&[tag("a", &"v".repeat(257))]).is_err());
+
+        let many: Vec<xml::Tag> = (0..11).map(|i| tag(&i.to_string(), "")).collect();
+        assert!(validate_tags(&many).is_err());
+        assert!(validate_tags(&many[..10]).is_ok());
+    }
+}
@@ -26,11 +26,11 @@ pub struct BucketInfo {
     pub creation_date: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Owner {
     #[serde(rename = "ID")]
     pub id: String,
-    #[serde(rename = "DisplayName")]
+    #[serde(rename = "DisplayName", default)]
     pub display_name: String,
 }
 
@@ -65,6 +65,40 @@ pub struct ListBucketResult {
         skip_serializing_if = "Option::is_none"
     )]
     pub next_continuation_token: Option<String>,
+    #[serde(rename = "StartAfter", skip_serializing_if = "Option::is_none")]
+    pub start_after: Option<String>,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<String>,
+}
+
+/// ListObjects (V1) response
+#[derive(Debug, Serialize)]
+#[serde(rename = "ListBucketResult")]
+pub struct ListBucketResultV1 {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+    #[serde(rename = "Marker")]
+    pub marker: String,
+    #[serde(rename = "NextMarker", skip_serializing_if = "Option::is_none")]
+    pub next_marker: Option<String>,
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    pub max_keys: i32,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<String>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "Contents", default)]
+    pub contents: Vec<ObjectInfo>,
+    #[serde(
+        rename = "CommonPrefixes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub common_prefixes: Vec<CommonPrefix>,
 }
 
 #[derive(Debug, Serialize)]
@@ -113,6 +147,55 @@ pub struct CompleteMultipartUploadResult {
     pub etag: String,
 }
 
+/// POST Object response, returned when the form asks for success_action_status 201
+#[derive(Debug, Serialize)]
+#[serde(rename = "PostResponse")]
+pub struct PostResponse {
+    #[serde(rename = "Location")]
+    pub location: String,
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+/// GetObjectAttributes response; only the requested attributes are present
+#[derive(Debug, Serialize)]
+#[serde(rename = "GetObjectAttributesResponse")]
+pub struct GetObjectAttributesResponse {
+    #[serde(rename = "ETag", skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(rename = "Checksum", skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<ObjectChecksum>,
+    #[serde(rename = "ObjectParts", skip_serializing_if = "Option::is_none")]
+    pub object_parts: Option<ObjectParts>,
+    #[serde(rename = "StorageClass", skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    #[serde(rename = "ObjectSize", skip_serializing_if = "Option::is_none")]
+    pub object_size: Option<i64>,
+}
+
+/// An object's checksum; exactly one field is set
+#[derive(Debug, Default, Serialize)]
+pub struct ObjectChecksum {
+    #[serde(rename = "ChecksumCRC32", skip_serializing_if = "Option::is_none")]
+    pub crc32: Option<String>,
+    #[serde(rename = "ChecksumCRC32C", skip_serializing_if = "Option::is_none")]
+    pub crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA1", skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(rename = "ChecksumSHA256", skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectParts {
+    #[serde(rename = "TotalPartsCount")]
+    pub total_parts_count: usize,
+}
+
 /// CopyObject response
 #[derive(Debug, Serialize)]
 #[serde(rename = "CopyObjectResult")]
@@ -123,6 +206,120 @@ pub struct CopyObjectResult {
     pub etag: String,
 }
 
+/// UploadPartCopy response
+#[derive(Debug, Serialize)]
+#[serde(rename = "CopyPartResult")]
+pub struct CopyPartResult {
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+/// ListObjectVersions response
+#[derive(Debug, Serialize)]
+#[serde(rename = "ListVersionsResult")]
+pub struct ListVersionsResult {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+    #[serde(rename = "KeyMarker")]
+    pub key_marker: String,
+    #[serde(rename = "VersionIdMarker")]
+    pub version_id_marker: String,
+    #[serde(rename = "NextKeyMarker", skip_serializing_if = "Option::is_none")]
+    pub next_key_marker: Option<String>,
+    #[serde(
+        rename = "NextVersionIdMarker",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_version_id_marker: Option<String>,
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    pub max_keys: i32,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<String>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "Version", default)]
+    pub versions: Vec<ObjectVersion>,
+    #[serde(rename = "DeleteMarker", default)]
+    pub delete_markers: Vec<DeleteMarkerEntry>,
+    #[serde(
+        rename = "CommonPrefixes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectVersion {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: String,
+    #[serde(rename = "IsLatest")]
+    pub is_latest: bool,
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(rename = "Size")]
+    pub size: i64,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteMarkerEntry {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: String,
+    #[serde(rename = "IsLatest")]
+    pub is_latest: bool,
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+}
+
+/// DeleteObjects response
+#[derive(Debug, Serialize)]
+#[serde(rename = "DeleteResult")]
+pub struct DeleteResult {
+    #[serde(rename = "Deleted", default)]
+    pub deleted: Vec<DeletedObject>,
+    #[serde(rename = "Error", default)]
+    pub errors: Vec<DeleteError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletedObject {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId", skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(rename = "DeleteMarker", skip_serializing_if = "Option::is_none")]
+    pub delete_marker: Option<bool>,
+    #[serde(
+        rename = "DeleteMarkerVersionId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub delete_marker_version_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteError {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Message")]
+    pub message: String,
+}
+
 // ========== Request types ==========
 
 /// CompleteMultipartUpload request body
@@ -141,6 +338,24 @@ pub struct CompletePart {
     pub etag: String,
 }
 
+/// DeleteObjects request body
+#[derive(Debug, Deserialize)]
+#[serde(rename = "Delete")]
+pub struct DeleteRequest {
+    #[serde(rename = "Quiet", default)]
+    pub quiet: bool,
+    #[serde(rename = "Object", default)]
+    pub objects: Vec<ObjectIdentifier>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ObjectIdentifier {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: Option<String>,
+}
+
 /// CreateBucketConfiguration request body (optional)
 #[derive(Debug, Deserialize)]
 #[serde(rename = "CreateBucketConfiguration")]
@@ -149,6 +364,244 @@ pub struct CreateBucketConfiguration {
     pub location_constraint: Option<String>,
 }
 
+// ========== Bucket configuration types ==========
+
+/// GetBucketLocation response (GET /{bucket}?location)
+#[derive(Debug, Serialize)]
+#[serde(rename = "LocationConstraint")]
+pub struct LocationConstraint {
+    #[serde(rename = "$text")]
+    pub region: String,
+}
+
+/// Versioning configuration (PUT/GET /{bucket}?versioning)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename = "VersioningConfiguration")]
+pub struct VersioningConfiguration {
+    #[serde(rename = "Status", skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Lifecycle configuration (PUT/GET /{bucket}?lifecycle).
+/// Also persisted as JSON in `buckets.lifecycle`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "LifecycleConfiguration")]
+pub struct LifecycleConfiguration {
+    #[serde(rename = "Rule", default)]
+    pub rules: Vec<LifecycleRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleRule {
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "Filter", skip_serializing_if = "Option::is_none")]
+    pub filter: Option<LifecycleFilter>,
+    /// Legacy rule-level prefix (pre-Filter API)
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Expiration", skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<LifecycleExpiration>,
+    #[serde(
+        rename = "NoncurrentVersionExpiration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub noncurrent_version_expiration: Option<NoncurrentVersionExpiration>,
+    #[serde(
+        rename = "AbortIncompleteMultipartUpload",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub abort_incomplete_multipart_upload: Option<AbortIncompleteMultipartUpload>,
+}
+
+/// Rule filter: a prefix, a single tag, an object size bound, or an `And` of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleFilter {
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Tag", skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Tag>,
+    #[serde(
+        rename = "ObjectSizeGreaterThan",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub object_size_greater_than: Option<i64>,
+    #[serde(rename = "ObjectSizeLessThan", skip_serializing_if = "Option::is_none")]
+    pub object_size_less_than: Option<i64>,
+    #[serde(rename = "And", skip_serializing_if = "Option::is_none")]
+    pub and: Option<LifecycleAnd>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleAnd {
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Tag", default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+    #[serde(
+        rename = "ObjectSizeGreaterThan",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub object_size_greater_than: Option<i64>,
+    #[serde(rename = "ObjectSizeLessThan", skip_serializing_if = "Option::is_none")]
+    pub object_size_less_than: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+/// Expire current versions after a number of days or on a date,
+/// or remove delete markers that no longer hide any version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleExpiration {
+    #[serde(rename = "Days", skip_serializing_if = "Option::is_none")]
+    pub days: Option<i64>,
+    #[serde(rename = "Date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(
+        rename = "ExpiredObjectDeleteMarker",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoncurrentVersionExpiration {
+    #[serde(rename = "NoncurrentDays")]
+    pub noncurrent_days: i64,
+    /// Number of newest noncurrent versions to keep regardless of age
+    #[serde(
+        rename = "NewerNoncurrentVersions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub newer_noncurrent_versions: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbortIncompleteMultipartUpload {
+    #[serde(rename = "DaysAfterInitiation")]
+    pub days_after_initiation: i64,
+}
+
+/// Object tag set (PUT/GET /{bucket}/{key}?tagging)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Tagging")]
+pub struct Tagging {
+    #[serde(rename = "TagSet")]
+    pub tag_set: TagSet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSet {
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<Tag>,
+}
+
+/// Bucket ACL (PUT/GET /{bucket}?acl)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename = "AccessControlPolicy")]
+pub struct AccessControlPolicy {
+    #[serde(rename = "Owner", skip_serializing_if = "Option::is_none", default)]
+    pub owner: Option<Owner>,
+    #[serde(rename = "AccessControlList")]
+    pub access_control_list: AccessControlList,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessControlList {
+    #[serde(rename = "Grant", default)]
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Grant {
+    #[serde(rename = "Grantee")]
+    pub grantee: Grantee,
+    #[serde(rename = "Permission")]
+    pub permission: String,
+}
+
+/// A canonical user (`ID`) or a predefined group (`URI`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Grantee {
+    #[serde(rename = "@xmlns:xsi", default)]
+    pub xmlns_xsi: Option<String>,
+    /// Serialized with its prefix; quick-xml drops the prefix when deserializing
+    #[serde(rename = "@xsi:type", alias = "@type")]
+    pub grantee_type: String,
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    #[serde(
+        rename = "DisplayName",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub display_name: Option<String>,
+    #[serde(rename = "URI", skip_serializing_if = "Option::is_none", default)]
+    pub uri: Option<String>,
+}
+
+/// Bucket CORS configuration (PUT/GET /{bucket}?cors)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "CORSConfiguration")]
+pub struct CorsConfiguration {
+    #[serde(rename = "CORSRule", default)]
+    pub rules: Vec<CorsRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsRule {
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    #[serde(rename = "AllowedOrigin", default)]
+    pub allowed_origins: Vec<String>,
+    #[serde(rename = "AllowedMethod", default)]
+    pub allowed_methods: Vec<String>,
+    #[serde(rename = "AllowedHeader", default)]
+    pub allowed_headers: Vec<String>,
+    #[serde(rename = "ExposeHeader", default)]
+    pub expose_headers: Vec<String>,
+    #[serde(
+        rename = "MaxAgeSeconds",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub max_age_seconds: Option<u64>,
+}
+
+impl LifecycleRule {
+    pub fn is_enabled(&self) -> bool {
+        self.status == "Enabled"
+    }
+
+    /// Key prefix this rule applies to (empty = whole bucket)
+    pub fn key_prefix(&self) -> &str {
+        self.filter
+            .as_ref()
+            .and_then(|f| {
+                f.prefix
+                    .as_deref()
+                    .or(f.and.as_ref().and_then(|a| a.prefix.as_deref()))
+            })
+            .or(self.prefix.as_deref())
+            .unwrap_or("")
+    }
+
+    /// Whether the rule's filter selects objects by tag
+    pub fn has_tag_filter(&self) -> bool {
+        self.filter
+            .as_ref()
+            .is_some_and(|f| f.tag.is_some() || f.and.as_ref().is_some_and(|a| !a.tags.is_empty()))
+    }
+}
+
 // ========== XML helpers ==========
 
 /// Serialize a struct to XML string
@@ -162,3 +615,30 @@ pub fn to_xml<T: Serialize>(value: &T) -> anyhow::Result<String> {
 pub fn from_xml<'de, T: Deserialize<'de>>(xml: &'de str) -> anyhow::Result<T> {
     Ok(quick_xml::de::from_str(xml)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delete_request() {
+        let body = r#"<Delete xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <Quiet>true</Quiet>
+            <Object><Key>a.txt</Key></Object>
+            <Object><Key>b.txt</Key><VersionId>v1</VersionId></Object>
+        </Delete>"#;
+        let request: DeleteRequest = from_xml(body).unwrap();
+        assert!(request.quiet);
+        assert_eq!(request.objects.len(), 2);
+        assert_eq!(request.objects[0].key, "a.txt");
+        assert_eq!(request.objects[0].version_id, None);
+        assert_eq!(request.objects[1].version_id.as_deref(), Some("v1"));
+
+        // Quiet defaults to false
+        let request: DeleteRequest =
+            from_xml("<Delete><Object><Key>a.txt</Key></Object></Delete>").unwrap();
+        assert!(!request.quiet);
+
+        assert!(from_xml::<DeleteRequest>("<Delete><Object></Object></Delete>").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use anyhow::{anyhow, ensure, Result};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+use crate::storage::hasher;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length of the nonce stored in front of each encrypted chunk
+const NONCE_LEN: usize = 12;
+
+/// A customer-provided key (SSE-C). Chunks are encrypted with AES-256-GCM
+/// before they become drafts; only the key's MD5 is ever stored.
+///
+/// Chunks are identified by an HMAC of their contents under the key instead of
+/// a plain SHA256, so deduplication only matches chunks stored with the same
+/// key. The nonce is derived from that HMAC, so identical chunks still encrypt
+/// identically and can share a draft.
+#[derive(Clone)]
+pub struct CustomerKey {
+    hash_key: [u8; 32],
+    cipher_key: [u8; 32],
+    /// Base64 MD5 of the key, as in `x-amz-server-side-encryption-customer-key-MD5`
+    pub key_md5: String,
+}
+
+impl CustomerKey {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            hash_key: derive(key, b"objectmail chunk hash"),
+            cipher_key: derive(key, b"objectmail chunk encryption"),
+            key_md5: BASE64_STANDARD.encode(Md5::digest(key)),
+        }
+    }
+
+    /// Hex hash a chunk is deduplicated under
+    pub fn chunk_hash(&self, data: &[u8]) -> String {
+        hex::encode(self.mac(data))
+    }
+
+    /// ETag of data stored under this key: unlike a plain MD5, it doesn't
+    /// reveal anything about the contents to someone without the key
+    pub fn etag(&self, data: &[u8]) -> String {
+        format!("\"{}\"", hasher::compute_md5(&self.mac(data)))
+    }
+
+    /// Encrypt a chunk; the result is the nonce followed by the ciphertext
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mac = self.mac(data);
+        let nonce = Nonce::from_slice(&mac[..NONCE_LEN]);
+        let ciphertext = self
+            .cipher()
+            .encrypt(nonce, data)
+            .map_err(|_| anyhow!("Failed to encrypt chunk"))?;
+
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt a chunk produced by [`CustomerKey::encrypt`]
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        ensure!(sealed.len() >= NONCE_LEN, "Encrypted chunk is truncated");
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt chunk"))
+    }
+
+    fn mac(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.hash_key).unwrap();
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.cipher_key))
+    }
+}
+
+/// Separate subkey for each use of the customer key
+fn derive(key: &[u8; 32], label: &[u8]) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(key).unwrap();
+    mac.update(label);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key = CustomerKey::new(&[7u8; 32]);
+        let sealed = key.encrypt(b"hello world").unwrap();
+        assert_ne!(&sealed[NONCE_LEN..], b"hello world");
+        assert_eq!(key.decrypt(&sealed).unwrap(), b"hello world");
+
+        // Same key and data encrypt identically, so the chunk can be deduplicated
+        assert_eq!(key.encrypt(b"hello world").unwrap(), sealed);
+
+        let other = CustomerKey::new(&[8u8; 32]);
+        assert!(other.decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    fn test_chunk_hash_depends_on_key() {
+        let a = CustomerKey::new(&[1u8; 32]);
+        let b = CustomerKey::new(&[2u8; 32]);
+        assert_eq!(a.chunk_hash(b"data").len(), 64);
+        assert_eq!(a.chunk_hash(b"data"), a.chunk_hash(b"data"));
+        assert_ne!(a.chunk_hash(b"data"), b.chunk_hash(b"data"));
+        assert_ne!(a.chunk_hash(b"data"), hasher::compute_sha256(b"data"));
+        assert_eq!(a.key_md5.len(), 24);
+    }
+}
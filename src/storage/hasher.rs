@@ -1,4 +1,7 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
 use md5::{Digest as Md5Digest, Md5};
+use sha1::Sha1;
 use sha2::{Digest as ShaDigest, Sha256};
 
 /// Streaming hash result
@@ -37,6 +40,117 @@ pub fn compute_sha256(data: &[u8]) -> String {
     hex::encode(hasher.finalize())
 }
 
+/// Compute an S3-style multipart ETag from the part ETags:
+/// MD5 of the concatenated binary part digests, suffixed with the part count.
+/// A part's digest is the MD5 of its data only for plain UploadPart; parts staged
+/// by UploadPartCopy or encrypted with a customer key carry an opaque 128-bit
+/// digest, which is simply hashed along.
+pub fn compute_multipart_etag(part_etags: &[&str]) -> anyhow::Result<String> {
+    let mut hasher = Md5::new();
+    for etag in part_etags {
+        let digest = hex::decode(etag.trim_matches('"'))?;
+        hasher.update(&digest);
+    }
+    Ok(format!(
+        "\"{}-{}\"",
+        hex::encode(hasher.finalize()),
+        part_etags.len()
+    ))
+}
+
+/// Additional checksum algorithms a client can ask for (`x-amz-checksum-*`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Crc32c,
+    Sha1,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    pub const ALL: [ChecksumAlgorithm; 4] = [
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32c,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+    ];
+
+    /// Parse an algorithm name as used in `x-amz-sdk-checksum-algorithm` (e.g. "CRC32C")
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "CRC32",
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+        }
+    }
+
+    /// Header carrying a checksum of this type, e.g. `x-amz-checksum-crc32c`
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+            ChecksumAlgorithm::Sha1 => "x-amz-checksum-sha1",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+        }
+    }
+
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            ChecksumAlgorithm::Crc32 => crc32fast::hash(data).to_be_bytes().to_vec(),
+            ChecksumAlgorithm::Crc32c => crc32c::crc32c(data).to_be_bytes().to_vec(),
+            ChecksumAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+            ChecksumAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+/// A checksum value together with its algorithm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    /// Base64 digest, suffixed with `-<parts>` for multipart objects
+    pub value: String,
+}
+
+impl Checksum {
+    /// Rebuild a checksum from its `checksum_algorithm`/`checksum` columns
+    pub fn from_stored(algorithm: Option<&str>, value: Option<&str>) -> Option<Self> {
+        Some(Checksum {
+            algorithm: ChecksumAlgorithm::parse(algorithm?)?,
+            value: value?.to_string(),
+        })
+    }
+}
+
+/// Compute a checksum the way S3 reports it: base64 of the big-endian digest
+pub fn compute_checksum(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+    BASE64_STANDARD.encode(algorithm.digest(data))
+}
+
+/// Compute the checksum of a multipart object from its part checksums:
+/// the checksum of the concatenated binary part checksums, suffixed with the part count
+pub fn compute_composite_checksum(
+    algorithm: ChecksumAlgorithm,
+    part_checksums: &[&str],
+) -> anyhow::Result<String> {
+    let mut concatenated = Vec::new();
+    for checksum in part_checksums {
+        concatenated.extend(BASE64_STANDARD.decode(checksum)?);
+    }
+    Ok(format!(
+        "{}-{}",
+        compute_checksum(algorithm, &concatenated),
+        part_checksums.len()
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,4 +172,38 @@ mod tests {
         let result = compute_hashes(data);
         assert_eq!(result.md5, "d41d8cd98f00b204e9800998ecf8427e");
     }
+
+    #[test]
+    fn test_multipart_etag() {
+        let part1 = format!("\"{}\"", compute_md5(b"hello "));
+        let part2 = format!("\"{}\"", compute_md5(b"world"));
+        let etag = compute_multipart_etag(&[&part1, &part2]).unwrap();
+        assert_eq!(etag, "\"e09e4fd6265b36115fe3db32df945d84-2\"");
+    }
+
+    #[test]
+    fn test_checksums() {
+        let data = b"hello world";
+        let expected = [
+            (ChecksumAlgorithm::Crc32, "DUoRhQ=="),
+            (ChecksumAlgorithm::Crc32c, "yZRlqg=="),
+            (ChecksumAlgorithm::Sha1, "Kq5sNclPz7QV2+lfQIuc6R7oRu0="),
+            (
+                ChecksumAlgorithm::Sha256,
+                "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=",
+            ),
+        ];
+        for (algorithm, checksum) in expected {
+            assert_eq!(compute_checksum(algorithm, data), checksum);
+            assert_eq!(
+                ChecksumAlgorithm::parse(algorithm.as_str()),
+                Some(algorithm)
+            );
+        }
+        assert_eq!(
+            ChecksumAlgorithm::parse("crc32c"),
+            Some(ChecksumAlgorithm::Crc32c)
+        );
+        assert_eq!(ChecksumAlgorithm::parse("MD5"), None);
+    }
 }
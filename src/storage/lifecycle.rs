@@ -0,0 +1,226 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::s3::xml::{LifecycleExpiration, LifecycleRule, NoncurrentVersionExpiration};
+
+/// Tags stored on an object (`objects.tags`, a JSON object of key → value)
+pub fn object_tags(tags: Option<&serde_json::Value>) -> HashMap<String, String> {
+    tags.and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a rule's filter selects an object with this key, size and tags
+pub fn rule_matches(
+    rule: &LifecycleRule,
+    key: &str,
+    size: i64,
+    tags: &HashMap<String, String>,
+) -> bool {
+    if !key.starts_with(rule.key_prefix()) {
+        return false;
+    }
+    let Some(ref filter) = rule.filter else {
+        return true;
+    };
+
+    let (required_tags, greater_than, less_than) = match filter.and {
+        Some(ref and) => (
+            and.tags.iter().collect::<Vec<_>>(),
+            and.object_size_greater_than,
+            and.object_size_less_than,
+        ),
+        None => (
+            filter.tag.iter().collect(),
+            filter.object_size_greater_than,
+            filter.object_size_less_than,
+        ),
+    };
+
+    required_tags
+        .iter()
+        .all(|tag| tags.get(&tag.key) == Some(&tag.value))
+        && greater_than.is_none_or(|min| size > min)
+        && less_than.is_none_or(|max| size < max)
+}
+
+/// When an action measured in days takes effect: `days` after `since`,
+/// rounded up to the next midnight UTC (as S3 does)
+pub fn days_elapsed_at(since: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+    let due = since + Duration::days(days);
+    let midnight = due
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    if midnight == due {
+        due
+    } else {
+        midnight + Duration::days(1)
+    }
+}
+
+/// Parse a lifecycle `Date` (ISO 8601, e.g. `2025-01-01T00:00:00.000Z`)
+pub fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// Whether a current version created at `created_at` has expired at `now`
+pub fn is_expired(
+    expiration: &LifecycleExpiration,
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> bool {
+    if let Some(days) = expiration.days {
+        return now >= days_elapsed_at(created_at, days);
+    }
+    match expiration.date.as_deref().and_then(parse_date) {
+        Some(date) => now >= date,
+        None => false,
+    }
+}
+
+/// Indices of the versions of one key that a NoncurrentVersionExpiration removes.
+/// `created_newest_first` holds the creation times of all versions, current first;
+/// each noncurrent version became noncurrent when the next newer one was created.
+pub fn expired_noncurrent_versions(
+    created_newest_first: &[DateTime<Utc>],
+    action: &NoncurrentVersionExpiration,
+    now: DateTime<Utc>,
+) -> Vec<usize> {
+    let keep = action.newer_noncurrent_versions.unwrap_or(0);
+    (1..created_newest_first.len())
+        .filter(|&i| {
+            // i - 1 newer noncurrent versions precede this one
+            let noncurrent_since = created_newest_first[i - 1];
+            i > keep && now >= days_elapsed_at(noncurrent_since, action.noncurrent_days)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::xml::{LifecycleAnd, LifecycleFilter, Tag};
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_date(s).unwrap()
+    }
+
+    fn rule(filter: Option<LifecycleFilter>) -> LifecycleRule {
+        LifecycleRule {
+            id: None,
+            filter,
+            prefix: None,
+            status: "Enabled".to_string(),
+            expiration: None,
+            noncurrent_version_expiration: None,
+            abort_incomplete_multipart_upload: None,
+        }
+    }
+
+    #[test]
+    fn test_days_round_up_to_midnight() {
+        assert_eq!(
+            days_elapsed_at(at("2024-03-01T15:30:00Z"), 1),
+            at("2024-03-03T00:00:00Z")
+        );
+        assert_eq!(
+            days_elapsed_at(at("2024-03-01T00:00:00Z"), 1),
+            at("2024-03-02T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let by_days = LifecycleExpiration {
+            days: Some(30),
+            date: None,
+            expired_object_delete_marker: None,
+        };
+        let created = at("2024-01-01T12:00:00Z");
+        assert!(!is_expired(&by_days, created, at("2024-01-31T23:59:59Z")));
+        assert!(is_expired(&by_days, created, at("2024-02-01T00:00:00Z")));
+
+        let by_date = LifecycleExpiration {
+            days: None,
+            date: Some("2024-06-01T00:00:00.000Z".to_string()),
+            expired_object_delete_marker: None,
+        };
+        assert!(!is_expired(&by_date, created, at("2024-05-31T00:00:00Z")));
+        assert!(is_expired(&by_date, created, at("2024-06-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_rule_filters() {
+        let mut tags = HashMap::new();
+        tags.insert("class".to_string(), "log".to_string());
+        let log_tag = Tag {
+            key: "class".to_string(),
+            value: "log".to_string(),
+        };
+
+        assert!(rule_matches(&rule(None), "any/key", 10, &HashMap::new()));
+
+        let by_prefix = rule(Some(LifecycleFilter {
+            prefix: Some("logs/".to_string()),
+            tag: None,
+            object_size_greater_than: None,
+            object_size_less_than: None,
+            and: None,
+        }));
+        assert!(rule_matches(&by_prefix, "logs/a", 10, &tags));
+        assert!(!rule_matches(&by_prefix, "data/a", 10, &tags));
+
+        let by_tag_and_size = rule(Some(LifecycleFilter {
+            prefix: None,
+            tag: None,
+            object_size_greater_than: None,
+            object_size_less_than: None,
+            and: Some(LifecycleAnd {
+                prefix: Some("logs/".to_string()),
+                tags: vec![log_tag],
+                object_size_greater_than: Some(100),
+                object_size_less_than: None,
+            }),
+        }));
+        assert!(rule_matches(&by_tag_and_size, "logs/a", 101, &tags));
+        assert!(!rule_matches(&by_tag_and_size, "logs/a", 100, &tags));
+        assert!(!rule_matches(
+            &by_tag_and_size,
+            "logs/a",
+            101,
+            &HashMap::new()
+        ));
+        assert!(!rule_matches(&by_tag_and_size, "data/a", 101, &tags));
+    }
+
+    #[test]
+    fn test_expired_noncurrent_versions() {
+        let versions = [
+            at("2024-01-20T00:00:00Z"), // current
+            at("2024-01-10T00:00:00Z"), // noncurrent since 01-20
+            at("2024-01-05T00:00:00Z"), // noncurrent since 01-10
+            at("2024-01-01T00:00:00Z"), // noncurrent since 01-05
+        ];
+        let action = NoncurrentVersionExpiration {
+            noncurrent_days: 7,
+            newer_noncurrent_versions: None,
+        };
+        let now = at("2024-01-18T00:00:00Z");
+        assert_eq!(expired_noncurrent_versions(&versions, &action, now), [2, 3]);
+
+        let keep_two = NoncurrentVersionExpiration {
+            noncurrent_days: 7,
+            newer_noncurrent_versions: Some(2),
+        };
+        assert_eq!(expired_noncurrent_versions(&versions, &keep_two, now), [3]);
+        assert!(expired_noncurrent_versions(&versions[..1], &action, now).is_empty());
+    }
+}
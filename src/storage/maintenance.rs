@@ -0,0 +1,629 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::db::entities::{bucket, multipart_upload, object, session_credential};
+use crate::s3::listing;
+use crate::s3::xml::{
+    LifecycleConfiguration, LifecycleExpiration, LifecycleRule, NoncurrentVersionExpiration,
+};
+use crate::storage::lifecycle;
+use crate::storage::pipeline::NULL_VERSION;
+use crate::AppState;
+
+/// Number of candidate rows (or keys) examined per query by the lifecycle executor
+const EXPIRATION_PAGE_SIZE: u64 = 1000;
+
+/// Spawn the background maintenance loop.
+/// Runs once at startup and then every `storage.maintenance_interval_secs`.
+pub fn spawn(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let period =
+            std::time::Duration::from_secs(state.config.storage.maintenance_interval_secs.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_once(&state).await {
+                tracing::error!("Maintenance run failed: {:#}", e);
+            }
+        }
+    })
+}
+
+/// Run every maintenance task once
+async fn run_once(state: &AppState) -> Result<()> {
+    let aborted = expire_multipart_uploads(state).await?;
+    let removed = remove_orphaned_part_files(state).await?;
+    let expired = expire_objects(state).await?;
+    let purged = purge_expired_trash(state).await?;
+    let sessions = purge_expired_sessions(state).await?;
+
+    if aborted > 0 || removed > 0 || expired > 0 || purged > 0 || sessions > 0 {
+        tracing::info!(
+            "Maintenance: aborted {} stale multipart uploads, removed {} orphaned part files, \
+             expired {} objects, purged {} trashed objects, removed {} expired STS sessions",
+            aborted,
+            removed,
+            expired,
+            purged,
+            sessions
+        );
+    }
+    Ok(())
+}
+
+/// Apply the Expiration and NoncurrentVersionExpiration actions of every
+/// enabled lifecycle rule. Returns the number of objects, versions and
+/// delete markers removed.
+async fn expire_objects(state: &AppState) -> Result<usize> {
+    let now = Utc::now();
+    let buckets = bucket::Entity::find()
+        .filter(bucket::Column::Lifecycle.is_not_null())
+        .all(&state.db)
+        .await
+        .context("Failed to load bucket lifecycle configurations")?;
+
+    let mut expired = 0;
+    for b in buckets {
+        let Some(config) = b
+            .lifecycle
+            .clone()
+            .and_then(|v| serde_json::from_value::<LifecycleConfiguration>(v).ok())
+        else {
+            continue;
+        };
+
+        for rule in config.rules.iter().filter(|r| r.is_enabled()) {
+            if let Some(ref expiration) = rule.expiration {
+                expired += if expiration.expired_object_delete_marker == Some(true) {
+                    remove_expired_delete_markers(state, &b, rule).await?
+                } else {
+                    expire_current_versions(state, &b, rule, expiration, now).await?
+                };
+            }
+            if let Some(ref noncurrent) = rule.noncurrent_version_expiration {
+                expired += expire_noncurrent_versions(state, &b, rule, noncurrent, now).await?;
+            }
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Live objects of a bucket whose key starts with `prefix` and sorts after `after`
+fn objects_in_range(bucket_id: Uuid, prefix: &str, after: Option<&str>) -> Select<object::Entity> {
+    let mut query = object::Entity::find()
+        .filter(object::Column::BucketId.eq(bucket_id))
+        .filter(object::Column::DeletedAt.is_null())
+        .filter(object::Column::Key.gte(prefix));
+    if let Some(end) = listing::successor(prefix) {
+        query = query.filter(object::Column::Key.lt(end));
+    }
+    if let Some(after) = after {
+        query = query.filter(object::Column::Key.gt(after));
+    }
+    query
+}
+
+/// Delete keys or versions through the pipeline, returning how many were removed
+async fn delete_expired(
+    state: &AppState,
+    bucket_id: Uuid,
+    keys: &[(String, Option<String>)],
+) -> Result<usize> {
+    if keys.is_empty() {
+        return Ok(0);
+    }
+
+    let pipeline = state.pipeline.lock().await;
+    let results = pipeline.delete_keys(bucket_id, keys).await?;
+
+    let mut deleted = 0;
+    for ((key, _), result) in keys.iter().zip(results) {
+        match result {
+            Ok(_) => deleted += 1,
+            Err(e) => tracing::warn!("Failed to expire object '{}': {:#}", key, e),
+        }
+    }
+    Ok(deleted)
+}
+
+/// Expire current versions. In unversioned buckets the object is removed for
+/// good (bypassing the trash); in versioned buckets a delete marker is added.
+async fn expire_current_versions(
+    state: &AppState,
+    bucket: &bucket::Model,
+    rule: &LifecycleRule,
+    expiration: &LifecycleExpiration,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    // Nothing created after this can have expired yet
+    let created_before = match (expiration.days, expiration.date.as_deref()) {
+        (Some(days), _) => now - Duration::days(days),
+        (None, Some(date)) => match lifecycle::parse_date(date) {
+            Some(date) if date <= now => now,
+            _ => return Ok(0),
+        },
+        (None, None) => return Ok(0),
+    };
+    let version_id = bucket
+        .versioning
+        .is_none()
+        .then(|| NULL_VERSION.to_string());
+
+    let mut expired = 0;
+    let mut after: Option<String> = None;
+    loop {
+        let page = objects_in_range(bucket.id, rule.key_prefix(), after.as_deref())
+            .filter(object::Column::IsLatest.eq(true))
+            .filter(object::Column::IsDeleteMarker.eq(false))
+            .filter(object::Column::CreatedAt.lt(created_before))
+            .order_by_asc(object::Column::Key)
+            .limit(EXPIRATION_PAGE_SIZE)
+            .all(&state.db)
+            .await
+            .context("Failed to query objects for expiration")?;
+
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.key.clone());
+        let is_last_page = (page.len() as u64) < EXPIRATION_PAGE_SIZE;
+
+        let keys: Vec<(String, Option<String>)> = page
+            .into_iter()
+            .filter(|obj| {
+                let tags = lifecycle::object_tags(obj.tags.as_ref());
+                lifecycle::rule_matches(rule, &obj.key, obj.size, &tags)
+                    && lifecycle::is_expired(expiration, obj.created_at, now)
+            })
+            .map(|obj| (obj.key, version_id.clone()))
+            .collect();
+        expired += delete_expired(state, bucket.id, &keys).await?;
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Permanently remove noncurrent versions once they have been noncurrent for
+/// `NoncurrentDays`, keeping the `NewerNoncurrentVersions` most recent ones
+async fn expire_noncurrent_versions(
+    state: &AppState,
+    bucket: &bucket::Model,
+    rule: &LifecycleRule,
+    action: &NoncurrentVersionExpiration,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    // A version can only have been noncurrent that long if it was created before
+    let created_before = now - Duration::days(action.noncurrent_days);
+
+    let mut expired = 0;
+    let mut after: Option<String> = None;
+    loop {
+        let keys: Vec<String> = objects_in_range(bucket.id, rule.key_prefix(), after.as_deref())
+            .filter(object::Column::IsLatest.eq(false))
+            .filter(object::Column::CreatedAt.lt(created_before))
+            .select_only()
+            .column(object::Column::Key)
+            .distinct()
+            .order_by_asc(object::Column::Key)
+            .limit(EXPIRATION_PAGE_SIZE)
+            .into_tuple()
+            .all(&state.db)
+            .await
+            .context("Failed to query noncurrent versions")?;
+
+        let Some(last) = keys.last() else {
+            break;
+        };
+        after = Some(last.clone());
+        let is_last_page = (keys.len() as u64) < EXPIRATION_PAGE_SIZE;
+
+        let mut to_delete = Vec::new();
+        for key in keys {
+            let versions = object::Entity::find()
+                .filter(object::Column::BucketId.eq(bucket.id))
+                .filter(object::Column::Key.eq(&key))
+                .filter(object::Column::DeletedAt.is_null())
+                .order_by_desc(object::Column::CreatedAt)
+                .order_by_desc(object::Column::Id)
+                .all(&state.db)
+                .await
+                .context("Failed to load object versions")?;
+
+            let created: Vec<DateTime<Utc>> = versions.iter().map(|v| v.created_at).collect();
+            for i in lifecycle::expired_noncurrent_versions(&created, action, now) {
+                let version = &versions[i];
+                let tags = lifecycle::object_tags(version.tags.as_ref());
+                if lifecycle::rule_matches(rule, &version.key, version.size, &tags) {
+                    to_delete.push((version.key.clone(), Some(version.version_id.clone())));
+                }
+            }
+        }
+        expired += delete_expired(state, bucket.id, &to_delete).await?;
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Remove delete markers that are the only remaining version of their key
+async fn remove_expired_delete_markers(
+    state: &AppState,
+    bucket: &bucket::Model,
+    rule: &LifecycleRule,
+) -> Result<usize> {
+    let mut expired = 0;
+    let mut after: Option<String> = None;
+    loop {
+        let markers = objects_in_range(bucket.id, rule.key_prefix(), after.as_deref())
+            .filter(object::Column::IsLatest.eq(true))
+            .filter(object::Column::IsDeleteMarker.eq(true))
+            .order_by_asc(object::Column::Key)
+            .limit(EXPIRATION_PAGE_SIZE)
+            .all(&state.db)
+            .await
+            .context("Failed to query delete markers")?;
+
+        let Some(last) = markers.last() else {
+            break;
+        };
+        after = Some(last.key.clone());
+        let is_last_page = (markers.len() as u64) < EXPIRATION_PAGE_SIZE;
+
+        let marker_keys: Vec<&str> = markers.iter().map(|m| m.key.as_str()).collect();
+        let with_older_versions: HashSet<String> = object::Entity::find()
+            .filter(object::Column::BucketId.eq(bucket.id))
+            .filter(object::Column::DeletedAt.is_null())
+            .filter(object::Column::IsLatest.eq(false))
+            .filter(object::Column::Key.is_in(marker_keys))
+            .select_only()
+            .column(object::Column::Key)
+            .distinct()
+            .into_tuple::<String>()
+            .all(&state.db)
+            .await
+            .context("Failed to query object versions")?
+            .into_iter()
+            .collect();
+
+        let to_delete: Vec<(String, Option<String>)> = markers
+            .into_iter()
+            .filter(|m| !with_older_versions.contains(&m.key))
+            .map(|m| (m.key, Some(m.version_id)))
+            .collect();
+        expired += delete_expired(state, bucket.id, &to_delete).await?;
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Permanently delete objects that have been in the trash longer than the retention window
+async fn purge_expired_trash(state: &AppState) -> Result<usize> {
+    let cutoff = Utc::now() - Duration::hours(state.config.storage.trash_retention_hours as i64);
+
+    let mut purged = 0;
+    let mut after: Option<Uuid> = None;
+    loop {
+        let mut query = object::Entity::find().filter(object::Column::DeletedAt.lt(cutoff));
+        if let Some(after) = after {
+            query = query.filter(object::Column::Id.gt(after));
+        }
+        let expired: Vec<Uuid> = query
+            .select_only()
+            .column(object::Column::Id)
+            .order_by_asc(object::Column::Id)
+            .limit(EXPIRATION_PAGE_SIZE)
+            .into_tuple()
+            .all(&state.db)
+            .await
+            .context("Failed to query trashed objects")?;
+
+        let Some(&last) = expired.last() else {
+            break;
+        };
+        after = Some(last);
+        let is_last_page = (expired.len() as u64) < EXPIRATION_PAGE_SIZE;
+
+        let pipeline = state.pipeline.lock().await;
+        purged += pipeline.purge(&expired).await?;
+        drop(pipeline);
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Remove temporary credentials that have expired (they are already rejected)
+async fn purge_expired_sessions(state: &AppState) -> Result<usize> {
+    let result = session_credential::Entity::delete_many()
+        .filter(session_credential::Column::ExpiresAt.lt(Utc::now()))
+        .exec(&state.db)
+        .await
+        .context("Failed to purge expired STS sessions")?;
+    Ok(result.rows_affected as usize)
+}
+
+/// Abort multipart uploads that exceeded the global max age or a bucket's
+/// `AbortIncompleteMultipartUpload` lifecycle rule
+async fn expire_multipart_uploads(state: &AppState) -> Result<usize> {
+    let now = Utc::now();
+    let mut cutoffs = UploadCutoffs::new(state.config.storage.multipart_max_age_hours, now);
+
+    let buckets = bucket::Entity::find()
+        .filter(bucket::Column::Lifecycle.is_not_null())
+        .all(&state.db)
+        .await
+        .context("Failed to load bucket lifecycle configurations")?;
+    for b in buckets {
+        if let Some(config) = b
+            .lifecycle
+            .and_then(|v| serde_json::from_value::<LifecycleConfiguration>(v).ok())
+        {
+            cutoffs.add_rules(b.id, &config.rules, now);
+        }
+    }
+
+    let Some(latest_cutoff) = cutoffs.latest() else {
+        return Ok(0);
+    };
+
+    let candidates = multipart_upload::Entity::find()
+        .filter(multipart_upload::Column::CreatedAt.lt(latest_cutoff))
+        .all(&state.db)
+        .await
+        .context("Failed to query multipart uploads")?;
+
+    let mut aborted = 0;
+    for upload in candidates {
+        if !cutoffs.is_expired(&upload) {
+            continue;
+        }
+
+        let pipeline = state.pipeline.lock().await;
+        match pipeline.abort_multipart_upload(upload.id).await {
+            Ok(()) => aborted += 1,
+            Err(e) => tracing::warn!("Failed to abort stale upload {}: {:#}", upload.id, e),
+        }
+    }
+
+    Ok(aborted)
+}
+
+/// When multipart uploads expire: the global max age, and per bucket the
+/// `AbortIncompleteMultipartUpload` lifecycle rules as (prefix, cutoff) pairs
+#[derive(Debug, Default)]
+struct UploadCutoffs {
+    global: Option<DateTime<Utc>>,
+    rules: HashMap<Uuid, Vec<(String, DateTime<Utc>)>>,
+}
+
+impl UploadCutoffs {
+    /// Cutoffs with only the global max age (`0` = uploads never expire globally)
+    fn new(max_age_hours: u64, now: DateTime<Utc>) -> Self {
+        Self {
+            global: (max_age_hours > 0).then(|| now - Duration::hours(max_age_hours as i64)),
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Add a bucket's enabled abort rules
+    fn add_rules(&mut self, bucket_id: Uuid, rules: &[LifecycleRule], now: DateTime<Utc>) {
+        for rule in rules.iter().filter(|r| r.is_enabled()) {
+            if let Some(ref abort) = rule.abort_incomplete_multipart_upload {
+                self.rules.entry(bucket_id).or_default().push((
+                    rule.key_prefix().to_string(),
+                    now - Duration::days(abort.days_after_initiation),
+                ));
+            }
+        }
+    }
+
+    /// Only uploads older than the most permissive cutoff can possibly expire
+    fn latest(&self) -> Option<DateTime<Utc>> {
+        self.rules
+            .values()
+            .flatten()
+            .map(|(_, cutoff)| *cutoff)
+            .chain(self.global)
+            .max()
+    }
+
+    fn is_expired(&self, upload: &multipart_upload::Model) -> bool {
+        let expired_globally = self.global.is_some_and(|cutoff| upload.created_at < cutoff);
+        let expired_by_rule = self.rules.get(&upload.bucket_id).is_some_and(|rules| {
+            rules.iter().any(|(prefix, cutoff)| {
+                upload.key.starts_with(prefix) && upload.created_at < *cutoff
+            })
+        });
+        expired_globally || expired_by_rule
+    }
+}
+
+/// Remove part files in the temp dir whose upload no longer exists
+/// (e.g. left behind by a crash between writing the file and recording the part)
+async fn remove_orphaned_part_files(state: &AppState) -> Result<usize> {
+    let max_age_hours = state.config.storage.multipart_max_age_hours;
+    if max_age_hours == 0 {
+        return Ok(0);
+    }
+    let max_age = std::time::Duration::from_secs(max_age_hours * 3600);
+
+    let known_uploads: HashSet<Uuid> = multipart_upload::Entity::find()
+        .all(&state.db)
+        .await
+        .context("Failed to query multipart uploads")?
+        .into_iter()
+        .map(|u| u.id)
+        .collect();
+
+    let mut entries = match tokio::fs::read_dir(&state.config.storage.temp_dir).await {
+        Ok(entries) => entries,
+        Err(_) => return Ok(0),
+    };
+
+    let mut removed = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let age = entry
+            .metadata()
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|modified| modified.elapsed().ok());
+
+        if is_orphaned_part_file(file_name, age, max_age, &known_uploads)
+            && tokio::fs::remove_file(entry.path()).await.is_ok()
+        {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Whether a temp file is a part file ("{upload_id}-{part_number}") of an
+/// upload that no longer exists, old enough that it isn't being written
+fn is_orphaned_part_file(
+    file_name: &str,
+    age: Option<std::time::Duration>,
+    max_age: std::time::Duration,
+    known_uploads: &HashSet<Uuid>,
+) -> bool {
+    let Some(upload_id) = file_name
+        .rsplit_once('-')
+        .and_then(|(id, _)| Uuid::parse_str(id).ok())
+    else {
+        return false;
+    };
+    !known_uploads.contains(&upload_id) && age.is_some_and(|age| age > max_age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::xml::AbortIncompleteMultipartUpload;
+
+    fn upload(bucket_id: Uuid, key: &str, created_at: DateTime<Utc>) -> multipart_upload::Model {
+        multipart_upload::Model {
+            id: Uuid::new_v4(),
+            bucket_id,
+            key: key.to_string(),
+            content_type: None,
+            metadata: None,
+            created_at,
+            tags: None,
+            checksum_algorithm: None,
+            headers: None,
+            sse_customer_key_md5: None,
+        }
+    }
+
+    fn abort_rule(prefix: &str, days: i64) -> LifecycleRule {
+        LifecycleRule {
+            id: None,
+            filter: None,
+            prefix: Some(prefix.to_string()),
+            status: "Enabled".to_string(),
+            expiration: None,
+            noncurrent_version_expiration: None,
+            abort_incomplete_multipart_upload: Some(AbortIncompleteMultipartUpload {
+                days_after_initiation: days,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_global_upload_cutoff() {
+        let now = Utc::now();
+        let bucket_id = Uuid::new_v4();
+
+        let cutoffs = UploadCutoffs::new(24, now);
+        assert_eq!(cutoffs.latest(), Some(now - Duration::hours(24)));
+        assert!(!cutoffs.is_expired(&upload(bucket_id, "a", now - Duration::hours(23))));
+        assert!(cutoffs.is_expired(&upload(bucket_id, "a", now - Duration::hours(25))));
+
+        // No global max age and no rules: nothing ever expires
+        let cutoffs = UploadCutoffs::new(0, now);
+        assert_eq!(cutoffs.latest(), None);
+        assert!(!cutoffs.is_expired(&upload(bucket_id, "a", now - Duration::days(365))));
+    }
+
+    #[test]
+    fn test_rule_upload_cutoff() {
+        let now = Utc::now();
+        let bucket_id = Uuid::new_v4();
+        let mut disabled = abort_rule("", 1);
+        disabled.status = "Disabled".to_string();
+
+        let mut cutoffs = UploadCutoffs::new(0, now);
+        cutoffs.add_rules(bucket_id, &[abort_rule("tmp/", 2), disabled], now);
+        assert_eq!(cutoffs.latest(), Some(now - Duration::days(2)));
+
+        let old = now - Duration::days(3);
+        assert!(cutoffs.is_expired(&upload(bucket_id, "tmp/a", old)));
+        assert!(!cutoffs.is_expired(&upload(bucket_id, "tmp/a", now - Duration::days(1))));
+        // Outside the rule's prefix, or in another bucket
+        assert!(!cutoffs.is_expired(&upload(bucket_id, "data/a", old)));
+        assert!(!cutoffs.is_expired(&upload(Uuid::new_v4(), "tmp/a", old)));
+    }
+
+    #[test]
+    fn test_orphaned_part_files() {
+        let max_age = std::time::Duration::from_secs(3600);
+        let old = Some(max_age * 2);
+        let recent = Some(max_age / 2);
+        let live = Uuid::new_v4();
+        let known_uploads = HashSet::from([live]);
+        let orphan = format!("{}-3", Uuid::new_v4());
+
+        assert!(is_orphaned_part_file(&orphan, old, max_age, &known_uploads));
+        // Possibly still being written
+        assert!(!is_orphaned_part_file(
+            &orphan,
+            recent,
+            max_age,
+            &known_uploads
+        ));
+        assert!(!is_orphaned_part_file(
+            &orphan,
+            None,
+            max_age,
+            &known_uploads
+        ));
+        // Its upload row still exists
+        let live_part = format!("{}-3", live);
+        assert!(!is_orphaned_part_file(
+            &live_part,
+            old,
+            max_age,
+            &known_uploads
+        ));
+        // Not a part file
+        assert!(!is_orphaned_part_file(
+            "notes.txt",
+            old,
+            max_age,
+            &known_uploads
+        ));
+    }
+}
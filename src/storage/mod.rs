@@ -1,3 +1,6 @@
 pub mod chunker;
+pub mod encryption;
 pub mod hasher;
+pub mod lifecycle;
+pub mod maintenance;
 pub mod pipeline;
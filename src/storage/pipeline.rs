@@ -1,20 +1,39 @@
 use anyhow::{bail, Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, Set,
+    QueryFilter, QueryOrder, QuerySelect, Set,
 };
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 use crate::config::AppConfig;
-use crate::db::entities::{bucket, chunk, object};
+use crate::db::entities::{
+    bucket, chunk, multipart_part, multipart_part_chunk, multipart_upload, object,
+};
 use crate::email::metadata::ChunkMetadata;
 use crate::email::provider::EmailProvider;
 use crate::storage::chunker;
-use crate::storage::hasher;
+use crate::storage::encryption::CustomerKey;
+use crate::storage::hasher::{self, Checksum, ChecksumAlgorithm};
+
+/// Version id of objects written while versioning is not enabled
+pub const NULL_VERSION: &str = "null";
+
+/// Result of deleting a key or one version of it
+#[derive(Debug, Clone, Default)]
+pub struct DeleteOutcome {
+    /// Version that was deleted, or the delete marker that was created
+    pub version_id: Option<String>,
+    /// Whether that version is a delete marker
+    pub delete_marker: bool,
+}
 
 /// Orchestrates the full upload/download/delete flow between
 /// the S3 API layer, PostgreSQL metadata, and email draft storage.
@@ -42,6 +61,8 @@ impl StoragePipeline {
 
     /// Upload an object: buffer → hash → chunk → store as email drafts → record in DB
     /// Implements deduplication: reuses existing "active" chunks if hash matches.
+    /// With a customer key (SSE-C), chunks are encrypted before they become drafts.
+    #[allow(clippy::too_many_arguments)]
     pub async fn upload(
         &self,
         bucket_id: Uuid,
@@ -49,9 +70,15 @@ impl StoragePipeline {
         data: &[u8],
         content_type: &str,
         metadata_json: Option<serde_json::Value>,
+        headers_json: Option<serde_json::Value>,
+        tags: Option<serde_json::Value>,
+        checksum: Option<Checksum>,
+        encryption: Option<&CustomerKey>,
     ) -> Result<object::Model> {
-        let hashes = hasher::compute_hashes(data);
-        let etag = format!("\"{}\"", hashes.md5);
+        let etag = match encryption {
+            Some(encryption) => encryption.etag(data),
+            None => format!("\"{}\"", hasher::compute_hashes(data).md5),
+        };
         let total_size = data.len() as u64;
 
         // Chunk the data
@@ -59,124 +86,60 @@ impl StoragePipeline {
         let chunks = chunker::chunk_data(data, chunk_size);
         let total_chunks = chunks.len() as u32;
 
-        // Delete existing object if it exists (overwrite semantics)
-        self.delete_by_key(bucket_id, key).await.ok();
+        // Overwrite semantics: replace or demote the current version
+        let version_id = self.prepare_new_version(bucket_id, key).await?;
 
         // Create object record
-        let object_id = Uuid::new_v4();
-        let now = Utc::now();
-
-        let obj = object::ActiveModel {
-            id: Set(object_id),
-            bucket_id: Set(bucket_id),
-            key: Set(key.to_string()),
-            size: Set(total_size as i64),
-            etag: Set(etag.clone()),
-            content_type: Set(content_type.to_string()),
-            metadata: Set(metadata_json),
-            chunk_count: Set(total_chunks as i32),
-            created_at: Set(now),
-            updated_at: Set(now),
-        };
-
-        let obj = obj
-            .insert(&self.db)
-            .await
-            .context("Failed to insert object record")?;
+        let obj = self
+            .insert_object(
+                bucket_id,
+                key,
+                &version_id,
+                total_size as i64,
+                &etag,
+                content_type,
+                metadata_json,
+                headers_json,
+                tags,
+                checksum,
+                encryption.map(|e| e.key_md5.clone()),
+                total_chunks as i32,
+            )
+            .await?;
+        let object_id = obj.id;
 
         // Upload each chunk as an email draft
         for chunk_data in &chunks {
-            // Deduplication: Check for existing active chunk with same hash
-            let existing_chunk = chunk::Entity::find()
-                .filter(chunk::Column::Hash.eq(&chunk_data.hash))
-                .filter(chunk::Column::Status.eq("active"))
-                .one(&self.db)
-                .await
-                .context("Failed to check for duplicate chunks")?;
-
-            let (draft_uid, is_reused) = if let Some(existing) = existing_chunk {
-                tracing::info!(
-                    "Deduplication hit: Reusing chunk hash {} (uid {})",
-                    chunk_data.hash,
-                    existing.draft_uid
-                );
-                (existing.draft_uid, true)
-            } else {
-                // Try to recycle a 'free' chunk from the pool
-                let free_chunk = chunk::Entity::find()
-                    .filter(chunk::Column::Status.eq("free"))
-                    .one(&self.db)
-                    .await
-                    .context("Failed to check for free chunks")?;
-
-                let meta = ChunkMetadata {
-                    v: 1,
-                    bucket: key.to_string(),
-                    key: key.to_string(),
-                    chunk_idx: chunk_data.index,
-                    total_chunks,
-                    object_id: object_id.to_string(),
-                    chunk_hash: chunk_data.hash.clone(),
-                    total_size,
-                    content_type: content_type.to_string(),
-                };
-
-                let subject = meta
-                    .encode_subject()
-                    .context("Failed to encode chunk metadata")?;
-
-                if let Some(free) = free_chunk {
-                    tracing::info!("Recycling free chunk slot (old uid {})", free.draft_uid);
-                    // To "recycle" in IMAP, we must append new and delete old
-                    // (This keeps the total count exactly the same after the operation)
-                    let new_uid = self
-                        .email
-                        .create_draft(&subject, &chunk_data.data)
-                        .await
-                        .context("Failed to create draft during recycling")?;
-
-                    self.email.delete_draft(free.draft_uid as u32).await.ok(); // Ignore if old one is already gone
-
-                    // Delete the free chunk record so we can create a new active one
-                    chunk::Entity::delete_by_id(free.id)
-                        .exec(&self.db)
-                        .await
-                        .ok();
-
-                    (new_uid as i32, false)
-                } else {
-                    // No existing chunk and no free slots, upload new
-                    let new_uid = match self.email.create_draft(&subject, &chunk_data.data).await {
-                        Ok(uid) => uid,
-                        Err(e) => {
-                            return Err(e).context(format!(
-                                "Failed to create draft for chunk {}",
-                                chunk_data.index
-                            ));
-                        }
-                    };
-                    (new_uid as i32, false)
-                }
+            let (hash, stored) = seal_chunk(&chunk_data.data, &chunk_data.hash, encryption)?;
+            let meta = ChunkMetadata {
+                v: 1,
+                bucket: key.to_string(),
+                key: key.to_string(),
+                chunk_idx: chunk_data.index,
+                total_chunks,
+                object_id: object_id.to_string(),
+                chunk_hash: hash.clone(),
+                total_size,
+                content_type: content_type.to_string(),
             };
 
-            // Record chunk in DB
-            let chunk_record = chunk::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                object_id: Set(object_id),
-                chunk_index: Set(chunk_data.index as i32),
-                size: Set(chunk_data.size as i64),
-                hash: Set(chunk_data.hash.clone()),
-                draft_uid: Set(draft_uid),
-                email_account_id: Set(self.email_account_id),
-                status: Set("active".to_string()),
-                created_at: Set(now),
-                updated_at: Set(now),
-            };
-
-            chunk_record
-                .insert(&self.db)
+            let draft_uid = self
+                .store_chunk(&stored, &hash, &meta)
                 .await
-                .context("Failed to insert chunk record")?;
+                .context(format!(
+                    "Failed to create draft for chunk {}",
+                    chunk_data.index
+                ))?;
+
+            // Record chunk in DB
+            self.insert_chunk_record(
+                object_id,
+                chunk_data.index,
+                chunk_data.size as i64,
+                &hash,
+                draft_uid,
+            )
+            .await?;
         }
 
         tracing::info!(
@@ -187,11 +150,17 @@ impl StoragePipeline {
             etag
         );
 
+        self.trim_free_pool_logged().await;
         Ok(obj)
     }
 
-    /// Download an object: look up chunks in DB → fetch from email drafts → concatenate
-    pub async fn download(&self, object_id: Uuid) -> Result<Vec<u8>> {
+    /// Download an object: look up chunks in DB → fetch from email drafts → concatenate.
+    /// Chunks of an SSE-C object are decrypted with `encryption`.
+    pub async fn download(
+        &self,
+        object_id: Uuid,
+        encryption: Option<&CustomerKey>,
+    ) -> Result<Vec<u8>> {
         // Get all chunks ordered by index
         let chunks = chunk::Entity::find()
             .filter(chunk::Column::ObjectId.eq(object_id))
@@ -214,6 +183,7 @@ impl StoragePipeline {
                     "Failed to fetch draft for chunk {}",
                     chunk_record.chunk_index
                 ))?;
+            let chunk_data = open_chunk(chunk_data, encryption)?;
 
             data.extend_from_slice(&chunk_data);
 
@@ -228,63 +198,138 @@ impl StoragePipeline {
         Ok(data)
     }
 
-    /// Delete an object: mark chunks as 'deleted' → if no other refs, delete email draft
-    pub async fn delete(&self, object_id: Uuid) -> Result<()> {
+    /// Delete a key, or one version of it, then trim the free pool.
+    /// In a versioned bucket, deleting a key without a version id adds a delete marker.
+    pub async fn delete_by_key(
+        &self,
+        bucket_id: Uuid,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<DeleteOutcome> {
+        let versioning = self.bucket_versioning(bucket_id).await?;
+        let outcome = self
+            .remove_key(bucket_id, versioning.as_deref(), key, version_id)
+            .await?;
+        self.trim_free_pool_logged().await;
+        Ok(outcome)
+    }
+
+    /// Delete several keys (or versions), trimming the free pool once at the end
+    /// so that excess drafts are expunged in a single IMAP round trip.
+    /// Returns one result per requested key, in order.
+    pub async fn delete_keys(
+        &self,
+        bucket_id: Uuid,
+        keys: &[(String, Option<String>)],
+    ) -> Result<Vec<Result<DeleteOutcome>>> {
+        let versioning = self.bucket_versioning(bucket_id).await?;
+
+        let mut results = Vec::with_capacity(keys.len());
+        for (key, version_id) in keys {
+            results.push(
+                self.remove_key(bucket_id, versioning.as_deref(), key, version_id.as_deref())
+                    .await,
+            );
+        }
+
+        self.trim_free_pool_logged().await;
+        Ok(results)
+    }
+
+    /// Delete a key or version without trimming the free pool
+    async fn remove_key(
+        &self,
+        bucket_id: Uuid,
+        versioning: Option<&str>,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<DeleteOutcome> {
+        // Permanently delete a specific version
+        if let Some(version_id) = version_id {
+            let removed = self.release_version(bucket_id, key, version_id).await?;
+            if removed.as_ref().is_some_and(|o| o.is_latest) {
+                self.promote_latest(bucket_id, key).await?;
+            }
+            return Ok(DeleteOutcome {
+                version_id: Some(version_id.to_string()),
+                delete_marker: removed.is_some_and(|o| o.is_delete_marker),
+            });
+        }
+
+        let marker_version = match versioning {
+            // Unversioned: the "null" version is the only one
+            None => {
+                if self.config.storage.trash_retention_hours > 0 {
+                    self.trash_version(bucket_id, key, NULL_VERSION).await?;
+                } else {
+                    self.release_version(bucket_id, key, NULL_VERSION).await?;
+                }
+                return Ok(DeleteOutcome::default());
+            }
+            Some("Enabled") => new_version_id(),
+            // Suspended: the delete marker replaces the "null" version
+            Some(_) => {
+                self.release_version(bucket_id, key, NULL_VERSION).await?;
+                NULL_VERSION.to_string()
+            }
+        };
+
+        self.demote_latest(bucket_id, key).await?;
+        self.insert_delete_marker(bucket_id, key, &marker_version)
+            .await?;
+
+        Ok(DeleteOutcome {
+            version_id: Some(marker_version),
+            delete_marker: true,
+        })
+    }
+
+    /// Remove an object's records, moving chunks nobody else references to the free pool
+    async fn release_object(&self, object_id: Uuid) -> Result<()> {
         let chunks = chunk::Entity::find()
             .filter(chunk::Column::ObjectId.eq(object_id))
             .all(&self.db)
             .await
             .context("Failed to query chunks for deletion")?;
 
-        // Process each chunk
+        // Process each chunk (identical chunks within one object share a draft)
+        let mut seen_hashes = HashSet::new();
         for chunk_record in &chunks {
-            // Check if ANY other active object uses this same hash
-            // We need to count usage of this hash where status='active' AND object_id != current
-            let usage_count = chunk::Entity::find()
-                .filter(chunk::Column::Hash.eq(&chunk_record.hash))
-                .filter(chunk::Column::Status.eq("active"))
-                .filter(chunk::Column::ObjectId.ne(object_id))
-                .count(&self.db)
-                .await
-                .context("Failed to check chunk usage")?;
+            if !seen_hashes.insert(chunk_record.hash.as_str()) {
+                continue;
+            }
 
-            if usage_count > 0 {
+            // Check if ANY other active object (or staged multipart part) uses this same hash
+            if self
+                .is_hash_referenced(&chunk_record.hash, Some(object_id))
+                .await?
+            {
                 tracing::info!(
-                    "Chunk hash {} is used by {} other objects. Preserving draft UID {}.",
+                    "Chunk hash {} is still referenced. Preserving draft UID {}.",
                     chunk_record.hash,
-                    usage_count,
                     chunk_record.draft_uid
                 );
                 // Just delete the DB record for this specific object's chunk map
                 // (Handled by delete_many below)
-                // Last reference. Recycling.
-                // Move to recycling object to prevent deletion
-                let recycling_object = self.get_or_create_recycling_object().await?;
-
-                let mut free_record: chunk::ActiveModel = chunk_record.clone().into();
-                free_record.object_id = Set(recycling_object.id);
-                free_record.status = Set("free".to_string());
-
-                // Assign a random/unique chunk index to avoid collision in the recycling bucket
-                // (Since we don't care about order for free chunks)
-                // Use nanoseconds from epoch as a simple unique-ish ID
-                let nanos = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_nanos();
-                // Mix with draft_uid to reduce collision chance further
-                let unique_index = ((nanos as i32) ^ (chunk_record.draft_uid as i32)).abs();
-
-                free_record.chunk_index = Set(unique_index);
-                free_record.updated_at = Set(Utc::now());
-
-                free_record
-                    .update(&self.db)
-                    .await
-                    .context("Failed to move chunk to recycling bin")?;
-
-                tracing::info!("Chunk UID {} moved to free pool", chunk_record.draft_uid);
+                continue;
             }
+
+            // Last reference. Recycling.
+            // Move to recycling object to prevent deletion
+            let recycling_object = self.get_or_create_recycling_object().await?;
+
+            let mut free_record: chunk::ActiveModel = chunk_record.clone().into();
+            free_record.object_id = Set(recycling_object.id);
+            free_record.status = Set("free".to_string());
+            free_record.chunk_index = Set(free_chunk_index(chunk_record.draft_uid));
+            free_record.updated_at = Set(Utc::now());
+
+            free_record
+                .update(&self.db)
+                .await
+                .context("Failed to move chunk to recycling bin")?;
+
+            tracing::info!("Chunk UID {} moved to free pool", chunk_record.draft_uid);
         }
 
         // Delete chunk records
@@ -304,40 +349,852 @@ impl StoragePipeline {
         Ok(())
     }
 
-    /// Delete an object by bucket_id and key
-    pub async fn delete_by_key(&self, bucket_id: Uuid, key: &str) -> Result<()> {
+    /// Make room for a new current version of `key` and return its version id.
+    /// With versioning enabled the current version becomes noncurrent;
+    /// otherwise the existing "null" version is replaced.
+    async fn prepare_new_version(&self, bucket_id: Uuid, key: &str) -> Result<String> {
+        let version_id = match self.bucket_versioning(bucket_id).await?.as_deref() {
+            Some("Enabled") => new_version_id(),
+            _ => {
+                self.release_version(bucket_id, key, NULL_VERSION).await?;
+                NULL_VERSION.to_string()
+            }
+        };
+
+        self.demote_latest(bucket_id, key).await?;
+        Ok(version_id)
+    }
+
+    /// Release one version of a key, returning it if it existed
+    async fn release_version(
+        &self,
+        bucket_id: Uuid,
+        key: &str,
+        version_id: &str,
+    ) -> Result<Option<object::Model>> {
         let obj = object::Entity::find()
             .filter(object::Column::BucketId.eq(bucket_id))
             .filter(object::Column::Key.eq(key))
+            .filter(object::Column::VersionId.eq(version_id))
+            .filter(object::Column::DeletedAt.is_null())
             .one(&self.db)
-            .await?;
+            .await
+            .context("Failed to query object version")?;
 
-        if let Some(obj) = obj {
-            self.delete(obj.id).await?;
+        if let Some(ref obj) = obj {
+            self.release_object(obj.id).await?;
         }
+        Ok(obj)
+    }
 
+    /// Move one version of a key to the trash. Its chunks stay referenced,
+    /// so the data survives until the object is restored or purged.
+    async fn trash_version(&self, bucket_id: Uuid, key: &str, version_id: &str) -> Result<()> {
+        object::Entity::update_many()
+            .col_expr(object::Column::DeletedAt, Expr::value(Utc::now()))
+            .col_expr(object::Column::IsLatest, Expr::value(false))
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(key))
+            .filter(object::Column::VersionId.eq(version_id))
+            .filter(object::Column::DeletedAt.is_null())
+            .exec(&self.db)
+            .await
+            .context("Failed to move object to trash")?;
+
+        tracing::info!("Object '{}' moved to trash", key);
+        Ok(())
+    }
+
+    /// Restore a trashed object as the current version of its key
+    pub async fn restore(&self, trashed: &object::Model) -> Result<object::Model> {
+        self.demote_latest(trashed.bucket_id, &trashed.key).await?;
+
+        let restored = restored_version(trashed, Utc::now())
+            .update(&self.db)
+            .await
+            .context("Failed to restore object")?;
+
+        tracing::info!("Object '{}' restored from trash", restored.key);
+        Ok(restored)
+    }
+
+    /// Permanently delete trashed objects, trimming the free pool once at the end.
+    /// Returns how many were purged.
+    pub async fn purge(&self, object_ids: &[Uuid]) -> Result<usize> {
+        let mut purged = 0;
+        for &object_id in object_ids {
+            match self.release_object(object_id).await {
+                Ok(()) => purged += 1,
+                Err(e) => tracing::warn!("Failed to purge object {}: {:#}", object_id, e),
+            }
+        }
+
+        self.trim_free_pool_logged().await;
+        Ok(purged)
+    }
+
+    /// Mark the current version of a key (if any) as noncurrent
+    async fn demote_latest(&self, bucket_id: Uuid, key: &str) -> Result<()> {
+        object::Entity::update_many()
+            .col_expr(object::Column::IsLatest, Expr::value(false))
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(key))
+            .filter(object::Column::IsLatest.eq(true))
+            .exec(&self.db)
+            .await
+            .context("Failed to demote current version")?;
+        Ok(())
+    }
+
+    /// Make the newest remaining version of a key (if any) the current one
+    async fn promote_latest(&self, bucket_id: Uuid, key: &str) -> Result<()> {
+        let newest = object::Entity::find()
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(key))
+            .filter(object::Column::DeletedAt.is_null())
+            .order_by_desc(object::Column::CreatedAt)
+            .order_by_desc(object::Column::Id)
+            .one(&self.db)
+            .await
+            .context("Failed to query object versions")?;
+
+        if let Some(newest) = newest {
+            let mut active: object::ActiveModel = newest.into();
+            active.is_latest = Set(true);
+            active
+                .update(&self.db)
+                .await
+                .context("Failed to promote version")?;
+        }
         Ok(())
     }
 
-    /// Copy an object (creates new chunks by downloading and re-uploading)
+    /// Versioning state of a bucket (None if never enabled)
+    async fn bucket_versioning(&self, bucket_id: Uuid) -> Result<Option<String>> {
+        let bucket = bucket::Entity::find_by_id(bucket_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query bucket")?
+            .context("Bucket not found")?;
+        Ok(bucket.versioning)
+    }
+
+    /// Copy an object (creates new chunks by downloading and re-uploading).
+    /// The copy gets `tags` rather than the source's tags, and keeps its checksum
+    /// and standard headers. The source is decrypted with `source_encryption`
+    /// and the copy encrypted with `encryption`, if given.
+    #[allow(clippy::too_many_arguments)]
     pub async fn copy(
         &self,
         source_object: &object::Model,
+        source_encryption: Option<&CustomerKey>,
         dest_bucket_id: Uuid,
         dest_key: &str,
+        tags: Option<serde_json::Value>,
+        encryption: Option<&CustomerKey>,
     ) -> Result<object::Model> {
-        let data = self.download(source_object.id).await?;
+        let data = self.download(source_object.id, source_encryption).await?;
         let metadata = source_object.metadata.clone();
+        let checksum = Checksum::from_stored(
+            source_object.checksum_algorithm.as_deref(),
+            source_object.checksum.as_deref(),
+        );
         self.upload(
             dest_bucket_id,
             dest_key,
             &data,
             &source_object.content_type,
             metadata,
+            source_object.headers.clone(),
+            tags,
+            checksum,
+            encryption,
         )
         .await
     }
 
+    /// Stage a part that copies `range` of an existing object (UploadPartCopy).
+    /// Chunks fully covered by the range are referenced rather than copied; only
+    /// partially covered chunks at the edges are fetched, sliced and stored as new drafts.
+    /// If the source and the upload are not encrypted with the same customer key,
+    /// every chunk is re-encrypted instead.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn stage_part_copy(
+        &self,
+        upload: &multipart_upload::Model,
+        part_number: i32,
+        source: &object::Model,
+        range: Range<u64>,
+        source_encryption: Option<&CustomerKey>,
+        encryption: Option<&CustomerKey>,
+    ) -> Result<multipart_part::Model> {
+        // Replace any previous part with the same number first, so its
+        // drafts are not released after we start referencing them again
+        self.remove_part(upload.id, part_number).await?;
+
+        let source_chunks = chunk::Entity::find()
+            .filter(chunk::Column::ObjectId.eq(source.id))
+            .order_by_asc(chunk::Column::ChunkIndex)
+            .all(&self.db)
+            .await
+            .context("Failed to query source chunks")?;
+
+        // Select chunks overlapping the range, with their byte offsets in the source
+        let mut overlapping = Vec::new();
+        let mut offset = 0u64;
+        for source_chunk in &source_chunks {
+            let chunk_range = offset..offset + source_chunk.size as u64;
+            offset = chunk_range.end;
+            if chunk_range.end > range.start && chunk_range.start < range.end {
+                overlapping.push((chunk_range, source_chunk));
+            }
+        }
+
+        if offset < range.end {
+            bail!(
+                "Copy range {}-{} exceeds source object size {}",
+                range.start,
+                range.end,
+                offset
+            );
+        }
+
+        let same_key = source.sse_customer_key_md5 == upload.sse_customer_key_md5;
+        let total_chunks = overlapping.len() as u32;
+        let mut staged = Vec::with_capacity(overlapping.len());
+        for (chunk_range, source_chunk) in overlapping {
+            if same_key && chunk_range.start >= range.start && chunk_range.end <= range.end {
+                staged.push((
+                    source_chunk.size,
+                    source_chunk.hash.clone(),
+                    source_chunk.draft_uid,
+                ));
+                continue;
+            }
+
+            // Partially covered (or under another key): fetch the draft and
+            // keep only the overlapping bytes
+            let data = self
+                .email
+                .get_draft(source_chunk.draft_uid as u32)
+                .await
+                .context(format!(
+                    "Failed to fetch draft for chunk {}",
+                    source_chunk.chunk_index
+                ))?;
+            let data = open_chunk(data, source_encryption)?;
+
+            let from = (range.start.max(chunk_range.start) - chunk_range.start) as usize;
+            let to = (range.end.min(chunk_range.end) - chunk_range.start) as usize;
+            let slice = data.get(from..to).context(format!(
+                "Draft UID {} is shorter than its recorded size",
+                source_chunk.draft_uid
+            ))?;
+
+            let (hash, stored) = seal_chunk(slice, &hasher::compute_sha256(slice), encryption)?;
+            let meta = ChunkMetadata {
+                v: 1,
+                bucket: upload.key.clone(),
+                key: upload.key.clone(),
+                chunk_idx: staged.len() as u32,
+                total_chunks,
+                object_id: upload.id.to_string(),
+                chunk_hash: hash.clone(),
+                total_size: range.end - range.start,
+                content_type: source.content_type.clone(),
+            };
+
+            let draft_uid = self.store_chunk(&stored, &hash, &meta).await?;
+            staged.push((slice.len() as i64, hash, draft_uid));
+        }
+
+        // The part ETag is opaque: an MD5 over the chunk hashes, not of the copied
+        // bytes, so the data never has to be downloaded. Clients can't check it
+        // against the range, and the multipart ETag built from it is opaque too.
+        let hash_list: String = staged.iter().map(|(_, hash, _)| hash.as_str()).collect();
+        let etag = format!("\"{}\"", hasher::compute_md5(hash_list.as_bytes()));
+        self.insert_staged_part(
+            upload.id,
+            part_number,
+            (range.end - range.start) as i64,
+            etag,
+            None,
+            staged,
+        )
+        .await
+    }
+
+    /// Stage an uploaded part of an SSE-C upload: its chunks are encrypted and
+    /// stored as drafts right away, so the plaintext is never written to disk.
+    pub async fn stage_part(
+        &self,
+        upload: &multipart_upload::Model,
+        part_number: i32,
+        data: &[u8],
+        etag: String,
+        checksum: Option<String>,
+        encryption: &CustomerKey,
+    ) -> Result<multipart_part::Model> {
+        self.remove_part(upload.id, part_number).await?;
+
+        let chunks = chunker::chunk_data(data, self.config.chunk_size_bytes());
+        let total_chunks = chunks.len() as u32;
+        let mut staged = Vec::with_capacity(chunks.len());
+        for chunk_data in &chunks {
+            let (hash, stored) = seal_chunk(&chunk_data.data, &chunk_data.hash, Some(encryption))?;
+            let meta = ChunkMetadata {
+                v: 1,
+                bucket: upload.key.clone(),
+                key: upload.key.clone(),
+                chunk_idx: chunk_data.index,
+                total_chunks,
+                object_id: upload.id.to_string(),
+                chunk_hash: hash.clone(),
+                total_size: data.len() as u64,
+                content_type: upload
+                    .content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+            };
+
+            let draft_uid = self.store_chunk(&stored, &hash, &meta).await?;
+            staged.push((chunk_data.size as i64, hash, draft_uid));
+        }
+
+        self.insert_staged_part(
+            upload.id,
+            part_number,
+            data.len() as i64,
+            etag,
+            checksum,
+            staged,
+        )
+        .await
+    }
+
+    /// Record a part whose chunks are already stored as drafts
+    /// (`staged` holds each chunk's size, hash and draft UID, in order)
+    async fn insert_staged_part(
+        &self,
+        upload_id: Uuid,
+        part_number: i32,
+        size: i64,
+        etag: String,
+        checksum: Option<String>,
+        staged: Vec<(i64, String, i32)>,
+    ) -> Result<multipart_part::Model> {
+        let part = multipart_part::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            upload_id: Set(upload_id),
+            part_number: Set(part_number),
+            size: Set(size),
+            etag: Set(etag),
+            temp_path: Set(None),
+            created_at: Set(Utc::now()),
+            checksum: Set(checksum),
+        };
+
+        let part = part
+            .insert(&self.db)
+            .await
+            .context("Failed to insert multipart part record")?;
+
+        for (index, (size, hash, draft_uid)) in staged.into_iter().enumerate() {
+            let part_chunk = multipart_part_chunk::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                part_id: Set(part.id),
+                chunk_index: Set(index as i32),
+                size: Set(size),
+                hash: Set(hash),
+                draft_uid: Set(draft_uid),
+            };
+
+            part_chunk
+                .insert(&self.db)
+                .await
+                .context("Failed to insert multipart part chunk record")?;
+        }
+
+        Ok(part)
+    }
+
+    /// Complete a multipart upload by assembling the object from `parts`, in order.
+    /// Parts uploaded as data are chunked and stored as drafts; staged parts
+    /// (UploadPartCopy, and every part of an SSE-C upload) reuse the drafts they reference. Unused parts are released.
+    pub async fn complete_multipart_upload(
+        &self,
+        upload: &multipart_upload::Model,
+        parts: &[multipart_part::Model],
+    ) -> Result<object::Model> {
+        let chunk_size = self.config.chunk_size_bytes();
+        let content_type = upload
+            .content_type
+            .clone()
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        let mut staged: HashMap<Uuid, Vec<multipart_part_chunk::Model>> = HashMap::new();
+        for part_chunk in multipart_part_chunk::Entity::find()
+            .filter(multipart_part_chunk::Column::PartId.is_in(parts.iter().map(|p| p.id)))
+            .order_by_asc(multipart_part_chunk::Column::ChunkIndex)
+            .all(&self.db)
+            .await
+            .context("Failed to query staged part chunks")?
+        {
+            staged
+                .entry(part_chunk.part_id)
+                .or_default()
+                .push(part_chunk);
+        }
+
+        let total_size: u64 = parts.iter().map(|p| p.size as u64).sum();
+        let total_chunks: u32 = parts
+            .iter()
+            .map(|p| match staged.get(&p.id) {
+                Some(refs) => refs.len() as u32,
+                None => (p.size as u64).div_ceil(chunk_size) as u32,
+            })
+            .sum();
+
+        let part_etags: Vec<&str> = parts.iter().map(|p| p.etag.as_str()).collect();
+        let etag = hasher::compute_multipart_etag(&part_etags)?;
+
+        // Overwrite semantics: replace or demote the current version
+        let version_id = self
+            .prepare_new_version(upload.bucket_id, &upload.key)
+            .await?;
+
+        let obj = self
+            .insert_object(
+                upload.bucket_id,
+                &upload.key,
+                &version_id,
+                total_size as i64,
+                &etag,
+                &content_type,
+                upload.metadata.clone(),
+                upload.headers.clone(),
+                upload.tags.clone(),
+                multipart_checksum(upload, parts)?,
+                upload.sse_customer_key_md5.clone(),
+                total_chunks as i32,
+            )
+            .await?;
+
+        let mut chunk_index = 0u32;
+        for part in parts {
+            if let Some(refs) = staged.get(&part.id) {
+                for part_chunk in refs {
+                    self.insert_chunk_record(
+                        obj.id,
+                        chunk_index,
+                        part_chunk.size,
+                        &part_chunk.hash,
+                        part_chunk.draft_uid,
+                    )
+                    .await?;
+                    chunk_index += 1;
+                }
+                continue;
+            }
+
+            let temp_path = part
+                .temp_path
+                .as_ref()
+                .context(format!("No temp path for part {}", part.part_number))?;
+            let part_data = tokio::fs::read(temp_path)
+                .await
+                .context(format!("Failed to read data for part {}", part.part_number))?;
+
+            for chunk_data in chunker::chunk_data(&part_data, chunk_size) {
+                let meta = ChunkMetadata {
+                    v: 1,
+                    bucket: upload.key.clone(),
+                    key: upload.key.clone(),
+                    chunk_idx: chunk_index,
+                    total_chunks,
+                    object_id: obj.id.to_string(),
+                    chunk_hash: chunk_data.hash.clone(),
+                    total_size,
+                    content_type: content_type.clone(),
+                };
+
+                let draft_uid = self
+                    .store_chunk(&chunk_data.data, &chunk_data.hash, &meta)
+                    .await
+                    .context(format!("Failed to create draft for chunk {}", chunk_index))?;
+
+                self.insert_chunk_record(
+                    obj.id,
+                    chunk_index,
+                    chunk_data.size as i64,
+                    &chunk_data.hash,
+                    draft_uid,
+                )
+                .await?;
+                chunk_index += 1;
+            }
+        }
+
+        // The object now references every draft it uses; release everything else
+        self.remove_multipart_upload(upload.id).await?;
+
+        tracing::info!(
+            "Multipart upload {} completed as '{}': {} bytes, {} parts, {} chunks",
+            upload.id,
+            upload.key,
+            total_size,
+            parts.len(),
+            total_chunks
+        );
+
+        self.trim_free_pool_logged().await;
+        Ok(obj)
+    }
+
+    /// Abort a multipart upload: remove part files, staged drafts, part records and the upload
+    pub async fn abort_multipart_upload(&self, upload_id: Uuid) -> Result<()> {
+        let part_count = self.remove_multipart_upload(upload_id).await?;
+
+        tracing::info!(
+            "Multipart upload {} aborted ({} parts discarded)",
+            upload_id,
+            part_count
+        );
+        Ok(())
+    }
+
+    /// Remove a single part (if present), e.g. before it is uploaded again
+    pub async fn remove_part(&self, upload_id: Uuid, part_number: i32) -> Result<()> {
+        let existing = multipart_part::Entity::find()
+            .filter(multipart_part::Column::UploadId.eq(upload_id))
+            .filter(multipart_part::Column::PartNumber.eq(part_number))
+            .one(&self.db)
+            .await
+            .context("Failed to query multipart part")?;
+
+        if let Some(part) = existing {
+            self.release_part(&part).await?;
+        }
+        Ok(())
+    }
+
+    /// Release every part of an upload and delete the upload record; returns the part count
+    async fn remove_multipart_upload(&self, upload_id: Uuid) -> Result<usize> {
+        let parts = multipart_part::Entity::find()
+            .filter(multipart_part::Column::UploadId.eq(upload_id))
+            .all(&self.db)
+            .await
+            .context("Failed to query multipart parts")?;
+
+        for part in &parts {
+            self.release_part(part).await?;
+        }
+
+        multipart_upload::Entity::delete_by_id(upload_id)
+            .exec(&self.db)
+            .await
+            .context("Failed to delete multipart upload record")?;
+
+        Ok(parts.len())
+    }
+
+    /// Delete a part's temp file and record; staged drafts no longer
+    /// referenced by anything else go to the free pool
+    async fn release_part(&self, part: &multipart_part::Model) -> Result<()> {
+        if let Some(ref temp_path) = part.temp_path {
+            tokio::fs::remove_file(temp_path).await.ok();
+        }
+
+        let staged = multipart_part_chunk::Entity::find()
+            .filter(multipart_part_chunk::Column::PartId.eq(part.id))
+            .all(&self.db)
+            .await
+            .context("Failed to query staged part chunks")?;
+
+        // Cascades to the staged chunk references
+        multipart_part::Entity::delete_by_id(part.id)
+            .exec(&self.db)
+            .await
+            .context("Failed to delete multipart part record")?;
+
+        let mut seen_hashes = HashSet::new();
+        for part_chunk in &staged {
+            if !seen_hashes.insert(part_chunk.hash.as_str())
+                || self.is_hash_referenced(&part_chunk.hash, None).await?
+            {
+                continue;
+            }
+
+            let recycling_object = self.get_or_create_recycling_object().await?;
+            let now = Utc::now();
+            let free_record = chunk::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                object_id: Set(recycling_object.id),
+                chunk_index: Set(free_chunk_index(part_chunk.draft_uid)),
+                size: Set(part_chunk.size),
+                hash: Set(part_chunk.hash.clone()),
+                draft_uid: Set(part_chunk.draft_uid),
+                email_account_id: Set(self.email_account_id),
+                status: Set("free".to_string()),
+                created_at: Set(now),
+                updated_at: Set(now),
+            };
+
+            free_record
+                .insert(&self.db)
+                .await
+                .context("Failed to move staged chunk to recycling bin")?;
+
+            tracing::info!(
+                "Staged chunk UID {} moved to free pool",
+                part_chunk.draft_uid
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Expunge free drafts beyond `storage.free_pool_max_chunks`, oldest first,
+    /// with a single batched IMAP delete
+    async fn trim_free_pool(&self) -> Result<()> {
+        let max_chunks = self.config.storage.free_pool_max_chunks;
+        if max_chunks == 0 {
+            return Ok(());
+        }
+
+        let free_count = chunk::Entity::find()
+            .filter(chunk::Column::Status.eq("free"))
+            .count(&self.db)
+            .await
+            .context("Failed to count free chunks")?;
+        if free_count <= max_chunks {
+            return Ok(());
+        }
+
+        let excess = chunk::Entity::find()
+            .filter(chunk::Column::Status.eq("free"))
+            .order_by_asc(chunk::Column::UpdatedAt)
+            .limit(free_count - max_chunks)
+            .all(&self.db)
+            .await
+            .context("Failed to query free chunks")?;
+
+        let uids: Vec<u32> = excess.iter().map(|c| c.draft_uid as u32).collect();
+        self.email
+            .delete_drafts(&uids)
+            .await
+            .context("Failed to delete excess free drafts")?;
+
+        chunk::Entity::delete_many()
+            .filter(chunk::Column::Id.is_in(excess.iter().map(|c| c.id)))
+            .exec(&self.db)
+            .await
+            .context("Failed to delete free chunk records")?;
+
+        tracing::info!("Expunged {} excess free drafts", uids.len());
+        Ok(())
+    }
+
+    /// Trim the free pool after an operation that already succeeded;
+    /// a failed trim is retried on the next one
+    async fn trim_free_pool_logged(&self) {
+        if let Err(e) = self.trim_free_pool().await {
+            tracing::warn!("Failed to trim free chunk pool: {:#}", e);
+        }
+    }
+
+    /// Store chunk data as an email draft and return its UID.
+    /// Reuses an existing "active" draft with the same hash, otherwise recycles
+    /// a 'free' draft slot or creates a new draft.
+    async fn store_chunk(&self, data: &[u8], hash: &str, meta: &ChunkMetadata) -> Result<i32> {
+        // Deduplication: Check for existing active chunk with same hash
+        let existing_chunk = chunk::Entity::find()
+            .filter(chunk::Column::Hash.eq(hash))
+            .filter(chunk::Column::Status.eq("active"))
+            .one(&self.db)
+            .await
+            .context("Failed to check for duplicate chunks")?;
+
+        if let Some(existing) = existing_chunk {
+            tracing::info!(
+                "Deduplication hit: Reusing chunk hash {} (uid {})",
+                hash,
+                existing.draft_uid
+            );
+            return Ok(existing.draft_uid);
+        }
+
+        // Try to recycle a 'free' chunk from the pool
+        let free_chunk = chunk::Entity::find()
+            .filter(chunk::Column::Status.eq("free"))
+            .one(&self.db)
+            .await
+            .context("Failed to check for free chunks")?;
+
+        let subject = meta
+            .encode_subject()
+            .context("Failed to encode chunk metadata")?;
+
+        if let Some(free) = free_chunk {
+            tracing::info!("Recycling free chunk slot (old uid {})", free.draft_uid);
+            // To "recycle" in IMAP, we must append new and delete old
+            // (This keeps the total count exactly the same after the operation)
+            let new_uid = self
+                .email
+                .create_draft(&subject, data)
+                .await
+                .context("Failed to create draft during recycling")?;
+
+            self.email.delete_draft(free.draft_uid as u32).await.ok(); // Ignore if old one is already gone
+
+            // Delete the free chunk record so we can create a new active one
+            chunk::Entity::delete_by_id(free.id)
+                .exec(&self.db)
+                .await
+                .ok();
+
+            Ok(new_uid as i32)
+        } else {
+            // No existing chunk and no free slots, upload new
+            let new_uid = self.email.create_draft(&subject, data).await?;
+            Ok(new_uid as i32)
+        }
+    }
+
+    /// Check whether a chunk hash is used by any active object (optionally
+    /// ignoring one object) or by a staged multipart part
+    async fn is_hash_referenced(&self, hash: &str, excluding_object: Option<Uuid>) -> Result<bool> {
+        let mut active = chunk::Entity::find()
+            .filter(chunk::Column::Hash.eq(hash))
+            .filter(chunk::Column::Status.eq("active"));
+        if let Some(object_id) = excluding_object {
+            active = active.filter(chunk::Column::ObjectId.ne(object_id));
+        }
+
+        let active_count = active
+            .count(&self.db)
+            .await
+            .context("Failed to check chunk usage")?;
+        if active_count > 0 {
+            return Ok(true);
+        }
+
+        let staged_count = multipart_part_chunk::Entity::find()
+            .filter(multipart_part_chunk::Column::Hash.eq(hash))
+            .count(&self.db)
+            .await
+            .context("Failed to check staged chunk usage")?;
+
+        Ok(staged_count > 0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_object(
+        &self,
+        bucket_id: Uuid,
+        key: &str,
+        version_id: &str,
+        size: i64,
+        etag: &str,
+        content_type: &str,
+        metadata_json: Option<serde_json::Value>,
+        headers_json: Option<serde_json::Value>,
+        tags: Option<serde_json::Value>,
+        checksum: Option<Checksum>,
+        sse_customer_key_md5: Option<String>,
+        chunk_count: i32,
+    ) -> Result<object::Model> {
+        let now = Utc::now();
+        let obj = object::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            bucket_id: Set(bucket_id),
+            key: Set(key.to_string()),
+            size: Set(size),
+            etag: Set(etag.to_string()),
+            content_type: Set(content_type.to_string()),
+            metadata: Set(metadata_json),
+            chunk_count: Set(chunk_count),
+            created_at: Set(now),
+            updated_at: Set(now),
+            version_id: Set(version_id.to_string()),
+            is_latest: Set(true),
+            is_delete_marker: Set(false),
+            deleted_at: Set(None),
+            tags: Set(tags),
+            checksum_algorithm: Set(checksum.as_ref().map(|c| c.algorithm.as_str().to_string())),
+            checksum: Set(checksum.map(|c| c.value)),
+            headers: Set(headers_json),
+            sse_customer_key_md5: Set(sse_customer_key_md5),
+        };
+
+        obj.insert(&self.db)
+            .await
+            .context("Failed to insert object record")
+    }
+
+    async fn insert_delete_marker(
+        &self,
+        bucket_id: Uuid,
+        key: &str,
+        version_id: &str,
+    ) -> Result<object::Model> {
+        let now = Utc::now();
+        let marker = object::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            bucket_id: Set(bucket_id),
+            key: Set(key.to_string()),
+            size: Set(0),
+            etag: Set(String::new()),
+            content_type: Set(String::new()),
+            metadata: Set(None),
+            chunk_count: Set(0),
+            created_at: Set(now),
+            updated_at: Set(now),
+            version_id: Set(version_id.to_string()),
+            is_latest: Set(true),
+            is_delete_marker: Set(true),
+            deleted_at: Set(None),
+            tags: Set(None),
+            checksum_algorithm: Set(None),
+            checksum: Set(None),
+            headers: Set(None),
+            sse_customer_key_md5: Set(None),
+        };
+
+        marker
+            .insert(&self.db)
+            .await
+            .context("Failed to insert delete marker")
+    }
+
+    async fn insert_chunk_record(
+        &self,
+        object_id: Uuid,
+        chunk_index: u32,
+        size: i64,
+        hash: &str,
+        draft_uid: i32,
+    ) -> Result<chunk::Model> {
+        let now = Utc::now();
+        let chunk_record = chunk::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            object_id: Set(object_id),
+            chunk_index: Set(chunk_index as i32),
+            size: Set(size),
+            hash: Set(hash.to_string()),
+            draft_uid: Set(draft_uid),
+            email_account_id: Set(self.email_account_id),
+            status: Set("active".to_string()),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        chunk_record
+            .insert(&self.db)
+            .await
+            .context("Failed to insert chunk record")
+    }
+
     async fn get_or_create_recycling_object(&self) -> Result<object::Model> {
         let bucket_name = "recycling-bin";
         let object_key = format!("free-chunks-{}", self.email_account_id);
@@ -358,6 +1215,7 @@ impl StoragePipeline {
                 owner_id: Set("system".to_string()),
                 region: Set("local".to_string()),
                 created_at: Set(chrono::Utc::now()),
+                ..Default::default()
             };
             let b = new_bucket.insert(&self.db).await?;
             b.id
@@ -390,3 +1248,124 @@ impl StoragePipeline {
         }
     }
 }
+
+/// Hash a chunk is deduplicated under and the bytes stored in its draft: the
+/// data and its SHA256 `hash`, or its encryption and keyed hash under the customer key
+fn seal_chunk<'a>(
+    data: &'a [u8],
+    hash: &str,
+    encryption: Option<&CustomerKey>,
+) -> Result<(String, Cow<'a, [u8]>)> {
+    match encryption {
+        Some(encryption) => Ok((
+            encryption.chunk_hash(data),
+            Cow::Owned(encryption.encrypt(data)?),
+        )),
+        None => Ok((hash.to_string(), Cow::Borrowed(data))),
+    }
+}
+
+/// Chunk data as fetched from its draft, decrypted if it was stored encrypted
+fn open_chunk(data: Vec<u8>, encryption: Option<&CustomerKey>) -> Result<Vec<u8>> {
+    match encryption {
+        Some(encryption) => encryption.decrypt(&data),
+        None => Ok(data),
+    }
+}
+
+/// Composite checksum of a completed multipart upload, if the upload asked for
+/// one and every part has one (parts staged by UploadPartCopy do not)
+fn multipart_checksum(
+    upload: &multipart_upload::Model,
+    parts: &[multipart_part::Model],
+) -> Result<Option<Checksum>> {
+    let Some(algorithm) = upload
+        .checksum_algorithm
+        .as_deref()
+        .and_then(ChecksumAlgorithm::parse)
+    else {
+        return Ok(None);
+    };
+    let Some(part_checksums) = parts
+        .iter()
+        .map(|p| p.checksum.as_deref())
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(Checksum {
+        algorithm,
+        value: hasher::compute_composite_checksum(algorithm, &part_checksums)?,
+    }))
+}
+
+/// Assign a random/unique chunk index to avoid collision in the recycling bucket
+/// (Since we don't care about order for free chunks)
+fn free_chunk_index(draft_uid: i32) -> i32 {
+    // Use nanoseconds from epoch as a simple unique-ish ID
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    // Mix with draft_uid to reduce collision chance further
+    ((nanos as i32) ^ draft_uid).abs()
+}
+
+/// Generate an opaque id for a new object version
+fn new_version_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Changes that bring a trashed object back as the current version of its key.
+/// It is dated as of the restore, since versions are ordered by `created_at`.
+fn restored_version(trashed: &object::Model, now: DateTime<Utc>) -> object::ActiveModel {
+    let mut active: object::ActiveModel = trashed.clone().into();
+    active.deleted_at = Set(None);
+    active.is_latest = Set(true);
+    active.created_at = Set(now);
+    active.updated_at = Set(now);
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_restored_version() {
+        let deleted_at = Utc::now() - Duration::hours(1);
+        let created_at = deleted_at - Duration::days(30);
+        let trashed = object::Model {
+            id: Uuid::new_v4(),
+            bucket_id: Uuid::new_v4(),
+            key: "a".to_string(),
+            size: 0,
+            etag: String::new(),
+            content_type: String::new(),
+            metadata: None,
+            chunk_count: 0,
+            created_at,
+            updated_at: created_at,
+            version_id: NULL_VERSION.to_string(),
+            is_latest: false,
+            is_delete_marker: false,
+            deleted_at: Some(deleted_at),
+            tags: None,
+            checksum_algorithm: None,
+            checksum: None,
+            headers: None,
+            sse_customer_key_md5: None,
+        };
+
+        let now = Utc::now();
+        let restored = restored_version(&trashed, now);
+        assert_eq!(restored.deleted_at, Set(None));
+        assert_eq!(restored.is_latest, Set(true));
+        // Newer than any version written while it was in the trash
+        assert_eq!(restored.created_at, Set(now));
+        assert_eq!(restored.updated_at, Set(now));
+        assert!(!restored.key.is_set());
+    }
+}